@@ -1,13 +1,41 @@
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, SetAuthority, set_authority, mint_to, spl_token::instruction::AuthorityType};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token::Token;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, MintTo, CloseAccount, SetAuthority, FreezeAccount, mint_to, transfer_checked, close_account, set_authority, freeze_account};
+use anchor_spl::token_interface::spl_token_2022::instruction::AuthorityType;
 use anchor_spl::associated_token;
+use anchor_spl::metadata::{
+    create_master_edition_v3, create_metadata_accounts_v3, set_and_verify_sized_collection_item,
+    verify_sized_collection_item, CreateMasterEditionV3, CreateMetadataAccountsV3, Metadata,
+    SetAndVerifySizedCollectionItem, VerifySizedCollectionItem,
+};
+use anchor_spl::metadata::mpl_token_metadata::accounts::MasterEdition;
+use anchor_spl::metadata::mpl_token_metadata::types::{Collection, CollectionDetails, Creator, DataV2};
+use anchor_spl::metadata::mpl_token_metadata::{MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_URI_LENGTH};
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
 
 declare_id!("9NVaiC6n62KnMtVYUCcfdDY1KdAFNyZmnopdhTcvHnwJ");
 
 pub const MAX_TITLE_LEN: usize = 64;
 pub const MAX_CID_LEN: usize = 128;
 pub const MAX_CONTRIBUTORS: usize = 16; 
+pub const MAX_STEMS: usize = 64;
+pub const MAX_ACCEPTED_MINTS: usize = 8;
+pub const MAX_FEE_RECIPIENTS: usize = 8;
+pub const MAX_HOLD_SECS: i64 = 30 * 24 * 60 * 60;
+pub const MAX_MEMO_LEN: usize = 64;
+pub const MAX_DISPLAY_NAME_LEN: usize = 32;
+pub const MAX_PROFILE_TRACKS: usize = 64;
+pub const MAX_RUN_TRACKS: usize = 128;
+pub const MAX_CATALOG_ADMINS: usize = 8;
+pub const MAX_RELEASE_TRACKS: usize = 32;
+pub const MAX_UPC_LEN: usize = 13;
+pub const STEM_NFT_SYMBOL: &str = "STEM";
+pub const BADGE_SYMBOL: &str = "CREDIT";
+// Compute units a distribution run needs left before it starts on another track
+pub const RUN_TRACK_COMPUTE_RESERVE: u64 = 80_000;
 
 #[program]
 pub mod muzica {
@@ -17,9 +45,8 @@ pub mod muzica {
     use super::*;
 
   
-    pub fn initialize_track(
-        ctx: Context<InitializeTrack>,
-        track_id: u64,
+    pub fn initialize_track<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeTrack<'info>>,
         title: String,
         cid: String,
         master_hash: [u8; 32],
@@ -31,12 +58,19 @@ pub mod muzica {
         require!(title.len() <= MAX_TITLE_LEN, ErrorCode::TitleTooLong);
         require!(cid.len() <= MAX_CID_LEN, ErrorCode::CidTooLong);
         require!(contributors.len() == shares_bps.len(), ErrorCode::InvalidArgs);
-        require!(contributors.len() > 0, ErrorCode::NoContributors);
+        require!(!contributors.is_empty(), ErrorCode::NoContributors);
         require!(contributors.len() <= MAX_CONTRIBUTORS, ErrorCode::TooManyContributors);
 
         let sum: u64 = shares_bps.iter().map(|s| *s as u64).sum();
         require!(sum == 10000, ErrorCode::InvalidShareTotal);
 
+        // Track ids are handed out by the authority's counter, so they run 0..track_count
+        let counter = &mut ctx.accounts.artist_counter;
+        let track_id = counter.track_count;
+        counter.authority = ctx.accounts.authority.key();
+        counter.track_count = track_id.checked_add(1).ok_or(ErrorCode::MathError)?;
+        counter.bump = ctx.bumps.artist_counter;
+
         track.authority = *ctx.accounts.authority.key;
         track.track_id = track_id;
         track.title = title;
@@ -47,6 +81,15 @@ pub mod muzica {
         track.stem_mints = Vec::new();
         track.royalty_version = 0;
         track.bump = ctx.bumps.track;
+        track.paused = false;
+        track.catalog = None;
+        track.stem_count = 0;
+        track.collection_mint = None;
+        track.royalty_stems = Vec::new();
+        track.catalog_share_cap = 0;
+
+        // Contributors with a profile get this track listed on it when their profile is passed
+        link_profiles(&track.key(), &track.contributors, ctx.remaining_accounts)?;
 
         emit!(TrackInitialized {
             track_id,
@@ -58,28 +101,226 @@ pub mod muzica {
         Ok(())
     }
 
-    pub fn stem_mint(ctx: Context<StemMint>, track_id: u64, stem_mint: Pubkey) -> Result<()> {
+    pub fn init_artist_counter(ctx: Context<InitArtistCounter>, start: u64) -> Result<()> {
+
+        // Authorities with tracks created before the counter existed start it past their
+        // highest legacy id, so initialize_track never lands on an occupied PDA
+
+        let counter = &mut ctx.accounts.artist_counter;
+        counter.authority = ctx.accounts.authority.key();
+        counter.track_count = start;
+        counter.bump = ctx.bumps.artist_counter;
+
+        emit!(ArtistCounterInitialized {
+            authority: counter.authority,
+            track_count: start,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_stem(
+        ctx: Context<AddStem>,
+        track_id: u64,
+        stem_type: StemType,
+        cid: String,
+        audio_hash: [u8; 32],
+        creator: Pubkey,
+    ) -> Result<()> {
+
+        require!(cid.len() <= MAX_CID_LEN, ErrorCode::CidTooLong);
 
         let track = &mut ctx.accounts.track;
         require!(track.track_id == track_id, ErrorCode::InvalidArgs);
-        require!(track.stem_mints.len() < 64, ErrorCode::TooManyStems);
-        track.stem_mints.push(stem_mint);
+
+        let stem_index = track.stem_count;
+        track.stem_count = stem_index.checked_add(1).ok_or(ErrorCode::MathError)?;
+
+        let stem = &mut ctx.accounts.stem;
+        stem.track = track.key();
+        stem.stem_index = stem_index;
+        stem.stem_type = stem_type;
+        stem.cid = cid;
+        stem.audio_hash = audio_hash;
+        stem.creator = creator;
+        stem.mint = None;
+        stem.retired = false;
+        stem.bump = ctx.bumps.stem;
+
+        emit!(StemAdded {
+            track: stem.track,
+            stem_index,
+            stem_type,
+            creator,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_stem(
+        ctx: Context<UpdateStem>,
+        track_id: u64,
+        stem_index: u32,
+        stem_type: Option<StemType>,
+        cid: Option<String>,
+        audio_hash: Option<[u8; 32]>,
+        creator: Option<Pubkey>,
+    ) -> Result<()> {
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let stem = &mut ctx.accounts.stem;
+        require!(stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+        require!(!stem.retired, ErrorCode::StemRetired);
+
+        if let Some(stem_type) = stem_type {
+            stem.stem_type = stem_type;
+        }
+        if let Some(cid) = cid {
+            require!(cid.len() <= MAX_CID_LEN, ErrorCode::CidTooLong);
+            stem.cid = cid;
+        }
+        if let Some(audio_hash) = audio_hash {
+            stem.audio_hash = audio_hash;
+        }
+        if let Some(creator) = creator {
+            stem.creator = creator;
+        }
+
+        emit!(StemUpdated {
+            track: stem.track,
+            stem_index,
+        });
+
+        Ok(())
+    }
+
+    pub fn retire_stem(ctx: Context<UpdateStem>, track_id: u64, stem_index: u32) -> Result<()> {
+
+        // Retired stems keep their record for provenance but can no longer be changed or linked
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let stem = &mut ctx.accounts.stem;
+        require!(stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+        require!(!stem.retired, ErrorCode::StemRetired);
+        stem.retired = true;
+
+        emit!(StemRetired {
+            track: stem.track,
+            stem_index,
+        });
+
+        Ok(())
+    }
+
+    pub fn stem_mint(ctx: Context<StemMint>, track_id: u64, stem_index: u32) -> Result<()> {
+
+        // Only 1-of-1 decimals-0 mints whose issuance the track controls can be linked: the mint
+        // authority must be the track PDA, and is revoked here, or the master edition of a mint
+        // created by mint_stem_nft for this stem. A mint revoked elsewhere proves nothing.
+        // The link PDA keyed by the mint stops it being linked to a second stem.
+
+        let track = &ctx.accounts.track;
+        let track_key = track.key();
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let mint = &ctx.accounts.mint;
+        require!(mint.supply == 1, ErrorCode::InvalidStemMint);
+        let program_minted = mint.key()
+            == Pubkey::find_program_address(
+                &[b"stem_mint".as_ref(), ctx.accounts.stem.key().as_ref()],
+                &crate::ID,
+            )
+            .0;
+        let master_edition = MasterEdition::find_pda(&mint.key()).0;
+        let controlled = |authority: Pubkey| {
+            authority == track_key || (program_minted && authority == master_edition)
+        };
+        let authority_ok = match mint.mint_authority {
+            COption::Some(authority) => controlled(authority),
+            COption::None => false,
+        };
+        require!(authority_ok, ErrorCode::InvalidStemMint);
+        let freeze_ok = match mint.freeze_authority {
+            COption::Some(authority) => controlled(authority),
+            COption::None => true,
+        };
+        require!(freeze_ok, ErrorCode::InvalidStemMint);
+
+        if mint.mint_authority == COption::Some(track_key) {
+            let track_id_in_bytes = track.track_id.to_le_bytes();
+            let seeds = &[
+                b"track".as_ref(),
+                track.authority.as_ref(),
+                &track_id_in_bytes,
+                &[track.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            let cpi_accounts = SetAuthority {
+                current_authority: track.to_account_info(),
+                account_or_mint: mint.to_account_info(),
+            };
+            set_authority(
+                CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer),
+                AuthorityType::MintTokens,
+                None,
+            )?;
+        }
+
+        let stem = &mut ctx.accounts.stem;
+        require!(stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+        require!(!stem.retired, ErrorCode::StemRetired);
+        require!(stem.mint.is_none(), ErrorCode::StemMintAlreadyLinked);
+
+        let stem_mint = mint.key();
+        stem.mint = Some(stem_mint);
+
+        let link = &mut ctx.accounts.stem_mint_link;
+        link.mint = stem_mint;
+        link.track = track_key;
+        link.stem_index = stem_index;
+        link.bump = ctx.bumps.stem_mint_link;
+
+        emit!(StemMintLinked {
+            track: stem.track,
+            stem_index,
+            mint: stem_mint,
+        });
 
         Ok(())
 
     }
 
-    pub fn update_shares(ctx: Context<UpdateShares>, track_id: u64, new_shares_bps: Vec<u16>, contributors: Vec<Pubkey>) -> Result<()> {
+    pub fn update_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateShares<'info>>,
+        track_id: u64,
+        new_shares_bps: Vec<u16>,
+        contributors: Vec<Pubkey>,
+    ) -> Result<()> {
 
         let track = &mut ctx.accounts.track;
         require!(track.track_id == track_id, ErrorCode::InvalidArgs);
         require!(new_shares_bps.len() == contributors.len(), ErrorCode::InvalidRecipientCount);
+        require!(!contributors.is_empty(), ErrorCode::NoContributors);
+        require!(contributors.len() <= MAX_CONTRIBUTORS, ErrorCode::TooManyContributors);
 
+        // Stem royalty positions belong to their NFT holders and are left as they are
         let sum: u64 = new_shares_bps.iter().map(|s| *s as u64).sum();
-        require!(sum == 10000, ErrorCode::InvalidShareTotal);
+        require!(sum + track.royalty_bps() == 10000, ErrorCode::InvalidShareTotal);
 
         let old_version = track.royalty_version;
+        let removed: Vec<Pubkey> = track.contributors
+            .iter()
+            .filter(|c| !contributors.contains(c))
+            .copied()
+            .collect();
         track.shares = new_shares_bps.clone();
+        track.contributors = contributors;
+
+        link_profiles(&track.key(), &track.contributors, ctx.remaining_accounts)?;
+        unlink_profiles(&track.key(), &removed, ctx.remaining_accounts)?;
 
         track.royalty_version = old_version.checked_add(1).unwrap();
 
@@ -114,416 +355,5497 @@ pub mod muzica {
         Ok(())
     }
 
-    pub fn escrow_deposit(ctx: Context<EscrowDeposit>, amount: u64, track_id: u64, authority: Pubkey) -> Result<()> {
+    pub fn escrow_deposit(
+        ctx: Context<EscrowDeposit>,
+        amount: u64,
+        track_id: u64,
+        authority: Pubkey,
+        reference: Option<Pubkey>,
+        memo: Option<String>,
+    ) -> Result<()> {
 
         require!(amount > 0, ErrorCode::InvalidAmount);
+        if let Some(memo) = &memo {
+            require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+        }
         require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
         require!(ctx.accounts.track.authority == authority, ErrorCode::InvalidArgs);
         require!(ctx.accounts.escrow_token_account.owner == ctx.accounts.track.key(), ErrorCode::InvalidTokenAccountOwner);
 
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.payer_token_account.to_account_info(),
-                to: ctx.accounts.escrow_token_account.to_account_info(),
-                authority: ctx.accounts.payer.to_account_info(),
-            };
+        // Only mints the authority registered via add_accepted_mint can be deposited,
+        // and only into the escrow recorded for that mint
+        let escrow_key = ctx.accounts.escrow_token_account.key();
+        let escrow_mint = ctx.accounts.escrow_token_account.mint;
+        ctx.accounts.mint_registry.find(&escrow_mint)
+            .filter(|entry| entry.escrow == escrow_key)
+            .ok_or(ErrorCode::MintNotAccepted)?;
+
+            let received = transfer_into_escrow(
+                &ctx.accounts.payer_token_account.to_account_info(),
+                &mut ctx.accounts.escrow_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                amount,
+            )?;
+
+            let registry = &mut ctx.accounts.mint_registry;
+            let entry = registry.find_mut(&escrow_mint).ok_or(ErrorCode::MintNotAccepted)?;
+            entry.total_deposited = entry.total_deposited.checked_add(received).ok_or(ErrorCode::MathError)?;
 
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-            anchor_spl::token::transfer(cpi_ctx, amount)?;
+            // Referenced deposits get a ledger entry so the reference and memo can be looked up
+            // later. It is released straight away since nothing is held.
+            let deposit_id = match &mut ctx.accounts.deposit {
+                Some(deposit) => {
+                    let deposit_id = registry.deposit_count;
+                    registry.deposit_count = deposit_id.checked_add(1).ok_or(ErrorCode::MathError)?;
+
+                    deposit.track = ctx.accounts.track.key();
+                    deposit.depositor = ctx.accounts.payer.key();
+                    deposit.mint = escrow_mint;
+                    deposit.deposit_id = deposit_id;
+                    deposit.amount = received;
+                    deposit.release_at = Clock::get()?.unix_timestamp;
+                    deposit.status = DepositStatus::Released;
+                    deposit.bump = ctx.bumps.deposit.ok_or(ErrorCode::MissingDepositRecord)?;
+                    deposit.reference = reference;
+                    deposit.memo = memo.clone().unwrap_or_default();
+                    Some(deposit_id)
+                }
+                None => {
+                    require!(reference.is_none() && memo.is_none(), ErrorCode::MissingDepositRecord);
+                    None
+                }
+            };
 
             emit!(EscrowDeposited {
                 track_id: ctx.accounts.track.track_id,
                 depositor: ctx.accounts.payer.key(),
-                amount,
+                amount: received,
                 mint: ctx.accounts.escrow_token_account.mint,
+                transfer_fee: amount - received,
+                deposit_id,
+                reference,
+                memo,
             });
 
         Ok(())
     }
 
 
-    pub fn escrow_distribute<'info>(ctx: Context<'_, '_, '_, 'info, EscrowDistribute<'info>>, amount: u64, track_id: u64) -> Result<()> {
-
-        //whenever you are reading from multiple accounts in a loop you have to clone the data you need first to avoid borrow checker issues
-        // trust me i tried for 2 days
+    pub fn escrow_deposit_held(
+        ctx: Context<EscrowDepositHeld>,
+        amount: u64,
+        track_id: u64,
+        authority: Pubkey,
+        hold_secs: i64,
+        reference: Option<Pubkey>,
+        memo: Option<String>,
+    ) -> Result<()> {
 
         require!(amount > 0, ErrorCode::InvalidAmount);
+        if let Some(memo) = &memo {
+            require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+        }
         require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(ctx.accounts.track.authority == authority, ErrorCode::InvalidArgs);
+        require!((0..=MAX_HOLD_SECS).contains(&hold_secs), ErrorCode::InvalidHoldPeriod);
         require!(ctx.accounts.escrow_token_account.owner == ctx.accounts.track.key(), ErrorCode::InvalidTokenAccountOwner);
 
-        let track = &ctx.accounts.track;
-        let total_bps: u64 = track.shares.iter().map(|s| *s as u64).sum();
-        require!(total_bps == 10000, ErrorCode::InvalidShareTotal);
-
-        // Clone all data we need. if you dont do it you run into borrow checker issues in the loop. like me
-        let contributors = track.contributors.clone();
-        let shares = track.shares.clone();
-        let track_bump = track.bump;
+        let escrow_key = ctx.accounts.escrow_token_account.key();
         let escrow_mint = ctx.accounts.escrow_token_account.mint;
+        ctx.accounts.mint_registry.find(&escrow_mint)
+            .filter(|entry| entry.escrow == escrow_key)
+            .ok_or(ErrorCode::MintNotAccepted)?;
 
-        // Get references to account infos
-        let escrow_account_info = ctx.accounts.escrow_token_account.to_account_info();
-        let track_account_info = ctx.accounts.track.to_account_info();
-        let token_program_info = ctx.accounts.token_program.to_account_info();
+        let received = transfer_into_escrow(
+            &ctx.accounts.payer_token_account.to_account_info(),
+            &mut ctx.accounts.escrow_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
 
-        for (i, contributor) in contributors.iter().enumerate() {
-            let share_bps = shares[i] as u64;
-            let share_amount = amount * share_bps / 10000;
+        let now = Clock::get()?.unix_timestamp;
+        let registry = &mut ctx.accounts.mint_registry;
+        let deposit_id = registry.deposit_count;
+        registry.deposit_count = deposit_id.checked_add(1).ok_or(ErrorCode::MathError)?;
 
-            if share_amount == 0 {
-                continue;
-            }
+        let entry = registry.find_mut(&escrow_mint).ok_or(ErrorCode::MintNotAccepted)?;
+        entry.total_deposited = entry.total_deposited.checked_add(received).ok_or(ErrorCode::MathError)?;
 
-            let contributor_token_account = anchor_spl::associated_token::get_associated_token_address(
-                contributor,
-                &escrow_mint,
-            );
+        // Held funds stay in the escrow but are excluded from distribution until released
+        let status = if hold_secs > 0 {
+            entry.held = entry.held.checked_add(received).ok_or(ErrorCode::MathError)?;
+            DepositStatus::Held
+        } else {
+            DepositStatus::Released
+        };
 
-            
-            let to_account = ctx.remaining_accounts
-                .iter()
-                .find(|acc| acc.key() == contributor_token_account)
-                .ok_or(ErrorCode::InvalidArgs)?;
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.track = ctx.accounts.track.key();
+        deposit.depositor = ctx.accounts.payer.key();
+        deposit.mint = escrow_mint;
+        deposit.deposit_id = deposit_id;
+        deposit.amount = received;
+        deposit.release_at = now.checked_add(hold_secs).ok_or(ErrorCode::MathError)?;
+        deposit.status = status;
+        deposit.bump = ctx.bumps.deposit;
+        deposit.reference = reference;
+        deposit.memo = memo.clone().unwrap_or_default();
 
-            let authority_key = ctx.accounts.authority.key();
-            let seeds = &[
-                b"track".as_ref(),
-                authority_key.as_ref(),
-                &track_id.to_le_bytes(),
-                &[track_bump],
-            ];
-            let signer = &[&seeds[..]];
+        emit!(EscrowDeposited {
+            track_id,
+            depositor: deposit.depositor,
+            amount: received,
+            mint: escrow_mint,
+            transfer_fee: amount - received,
+            deposit_id: Some(deposit_id),
+            reference,
+            memo,
+        });
 
-            let cpi_accounts = Transfer {
-                from: escrow_account_info.clone(),
-                to: to_account.clone(),
-                authority: track_account_info.clone(),
-            };
-            let cpi_ctx = CpiContext::new_with_signer(token_program_info.clone(), cpi_accounts, signer);
-            anchor_spl::token::transfer(cpi_ctx, share_amount)?;
-        }
+        emit!(DepositHeld {
+            track_id,
+            deposit_id,
+            release_at: deposit.release_at,
+        });
 
         Ok(())
     }
 
+    pub fn refund_deposit(ctx: Context<RefundDeposit>, track_id: u64, deposit_id: u64) -> Result<()> {
 
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
 
-    pub fn mint_stem_nft(ctx: Context<StemMintNFT>, track_id: u64, nft_index: u64) -> Result<()> {
-
-        let track = &mut ctx.accounts.track;
-        let track_authority = track.authority;
-        let track_bump = track.bump;
-        let mint_pubkey = ctx.accounts.mint.key();
-        let recipient = ctx.accounts.authority.key();
+        let deposit = &mut ctx.accounts.deposit;
+        require!(deposit.deposit_id == deposit_id, ErrorCode::InvalidArgs);
+        require!(deposit.status == DepositStatus::Held, ErrorCode::DepositNotHeld);
+        require!(Clock::get()?.unix_timestamp < deposit.release_at, ErrorCode::HoldPeriodElapsed);
 
-        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
-        
-        // Find the actual contributor index for the recipient
-        let actual_index = track.contributors
-            .iter()
-            .position(|c| c == &recipient)
-            .ok_or(ErrorCode::NotAContributor)? as u64;
-        
-        // Verify the passed index matches the actual contributor index
-        require!(nft_index == actual_index, ErrorCode::InvalidArgs);
+        let caller = ctx.accounts.caller.key();
+        require!(caller == deposit.depositor || caller == ctx.accounts.track.authority, ErrorCode::Unauthorized);
 
-        let track_id_in_bytes = track_id.to_le_bytes();
-        let seeds = &[
-            b"track".as_ref(),
-            track_authority.as_ref(),
-            &track_id_in_bytes,
-            &[track_bump],
-        ];
-        let signer = &[&seeds[..]];
+        let entry = ctx.accounts.mint_registry.find_mut(&deposit.mint).ok_or(ErrorCode::MintNotAccepted)?;
+        entry.held = entry.held.checked_sub(deposit.amount).ok_or(ErrorCode::MathError)?;
+        entry.total_deposited = entry.total_deposited.checked_sub(deposit.amount).ok_or(ErrorCode::MathError)?;
+        deposit.status = DepositStatus::Refunded;
 
-        let cpi_accounts_mint = anchor_spl::token::MintTo {
-            mint: ctx.accounts.mint.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: track.to_account_info(),
+        let source = EscrowSource {
+            track: &ctx.accounts.track,
+            escrow: &ctx.accounts.escrow_token_account.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            decimals: ctx.accounts.mint.decimals,
+            token_program: &ctx.accounts.token_program.to_account_info(),
         };
+        source.transfer(&ctx.accounts.depositor_token_account.to_account_info(), deposit.amount)?;
 
-        let cpi_program_mint = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx_mint = CpiContext::new_with_signer(
-            cpi_program_mint,
-            cpi_accounts_mint,
-            signer,
-        );
-        mint_to(cpi_ctx_mint, 1)?;
-
-        track.stem_mints.push(mint_pubkey);
-
-        emit!(StemNFTMinted {
-            track_id: track.track_id,
-            mint: mint_pubkey,
-            recipient: ctx.accounts.recipient_token_account.owner,
+        emit!(DepositRefunded {
+            track_id,
+            deposit_id,
+            depositor: deposit.depositor,
+            amount: deposit.amount,
         });
 
         Ok(())
-    
     }
 
+    pub fn release_deposit(ctx: Context<ReleaseDeposit>, track_id: u64, deposit_id: u64) -> Result<()> {
 
+        // Permissionless once the hold window closes, so anyone can crank held funds into distribution
 
-}
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
 
-    #[event]
-    pub struct StemNFTMinted {
-        pub track_id: u64,
-        pub mint: Pubkey,
-        pub recipient: Pubkey,
+        let deposit = &mut ctx.accounts.deposit;
+        require!(deposit.deposit_id == deposit_id, ErrorCode::InvalidArgs);
+        require!(deposit.status == DepositStatus::Held, ErrorCode::DepositNotHeld);
+        require!(Clock::get()?.unix_timestamp >= deposit.release_at, ErrorCode::HoldPeriodActive);
+
+        let entry = ctx.accounts.mint_registry.find_mut(&deposit.mint).ok_or(ErrorCode::MintNotAccepted)?;
+        entry.held = entry.held.checked_sub(deposit.amount).ok_or(ErrorCode::MathError)?;
+        deposit.status = DepositStatus::Released;
+
+        emit!(DepositReleased {
+            track_id,
+            deposit_id,
+            amount: deposit.amount,
+        });
+
+        Ok(())
     }
 
+    pub fn escrow_distribute<'info>(ctx: Context<'_, '_, '_, 'info, EscrowDistribute<'info>>, amount: u64, track_id: u64) -> Result<()> {
+        distribute_escrow(ctx, amount, track_id, Payout::Transfer)
+    }
 
+    // Same split as escrow_distribute, but contributor shares stay in the escrow as
+    // claimable balances that contributors sweep with claim_earnings
+    pub fn escrow_credit<'info>(ctx: Context<'_, '_, '_, 'info, EscrowDistribute<'info>>, amount: u64, track_id: u64) -> Result<()> {
+        distribute_escrow(ctx, amount, track_id, Payout::Credit)
+    }
 
-    #[derive(Accounts)]
-    #[instruction(track_id: u64, nft_index: u64)]
-    pub struct StemMintNFT<'info> {
+    pub fn claim_earnings<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimEarnings<'info>>) -> Result<()> {
 
-        #[account(mut)]
-        pub payer: Signer<'info>,
+        // remaining_accounts holds one group per track: [track, mint_registry, escrow, earnings].
+        // Claims stay open while the protocol or a track is paused so contributors can always exit.
 
-        // Track account - just verify it's mutable and matches the track_id
-        // We don't use PDA seeds here because the track was created by a different authority
-        // The client must pass the correct track address
-        #[account(
-            mut,
-            constraint = track.track_id == track_id @ ErrorCode::InvalidArgs,
-        )]
-        pub track: Account<'info, Track>,
+        require!(!ctx.remaining_accounts.is_empty(), ErrorCode::InvalidArgs);
+        let groups = ctx.remaining_accounts.chunks_exact(4);
+        require!(groups.remainder().is_empty(), ErrorCode::InvalidArgs);
 
-        #[account(
-            init,
-            payer = payer,
-            seeds = [
-                b"stem_mint".as_ref(),
-                track.key().as_ref(),
-                &nft_index.to_le_bytes(),
-            ],
-            bump,
-            mint::decimals = 0,
-            mint::authority = track,
-        )]
-        pub mint: Account<'info, Mint>,
+        let contributor = ctx.accounts.contributor.key();
+        let mint_key = ctx.accounts.mint.key();
+        let mut claimed_total: u64 = 0;
 
-        #[account(
-            init_if_needed,
-            payer = payer,
-            associated_token::mint = mint,
-            associated_token::authority = authority,
-        )]
-        pub recipient_token_account: Account<'info, TokenAccount>,
+        for group in groups {
+            let track = Account::<Track>::try_from(&group[0])?;
+            let registry_info = &group[1];
+            let escrow_info = &group[2];
+            let earnings_info = &group[3];
 
-        pub authority: Signer<'info>,
+            let (registry_address, _) = Pubkey::find_program_address(
+                &[b"mint_registry".as_ref(), track.key().as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(registry_info.key(), registry_address, ErrorCode::InvalidArgs);
 
-        pub token_program: Program<'info, Token>,
-        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
-        pub system_program: Program<'info, System>,
+            let (earnings_address, _) = Pubkey::find_program_address(
+                &[b"earnings".as_ref(), track.key().as_ref(), contributor.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(earnings_info.key(), earnings_address, ErrorCode::MissingEarningsAccount);
+            require_keys_eq!(*earnings_info.owner, crate::ID, ErrorCode::MissingEarningsAccount);
 
-    }
+            let mut registry = MintRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+            let entry = registry.find_mut(&mint_key).ok_or(ErrorCode::MintNotAccepted)?;
+            require_keys_eq!(escrow_info.key(), entry.escrow, ErrorCode::MissingEscrowAccount);
 
+            let mut earnings = ContributorEarnings::try_deserialize(&mut &earnings_info.try_borrow_data()?[..])?;
+            let earnings_entry = earnings.entry_mut(&mint_key)?;
+            let amount = earnings_entry.claimable;
+            if amount == 0 {
+                continue;
+            }
 
-    #[derive(Accounts)]
-    #[instruction(track_id: u64, authority: Pubkey)]
-    pub struct EscrowDistribute<'info> {
+            earnings_entry.claimable = 0;
+            earnings_entry.paid = earnings_entry.paid.checked_add(amount).ok_or(ErrorCode::MathError)?;
+            entry.credited_outstanding = entry.credited_outstanding.checked_sub(amount).ok_or(ErrorCode::MathError)?;
+            entry.total_distributed = entry.total_distributed.checked_add(amount).ok_or(ErrorCode::MathError)?;
 
-        #[account(
-            mut,
-            seeds = [
-                b"track".as_ref(), 
-                authority.key().as_ref(), 
-                track_id.to_le_bytes().as_ref()
-                ],
-            bump,
-            has_one = authority,
-        )]
-        pub track: Account<'info, Track>,
+            earnings.try_serialize(&mut &mut earnings_info.try_borrow_mut_data()?[..])?;
+            registry.try_serialize(&mut &mut registry_info.try_borrow_mut_data()?[..])?;
 
-        #[account(mut)]
-        pub escrow_token_account: Account<'info, TokenAccount>,
+            let source = EscrowSource {
+                track: &track,
+                escrow: escrow_info,
+                mint: &ctx.accounts.mint.to_account_info(),
+                decimals: ctx.accounts.mint.decimals,
+                token_program: &ctx.accounts.token_program.to_account_info(),
+            };
+            source.transfer(&ctx.accounts.contributor_token_account.to_account_info(), amount)?;
 
-        ///CHECK: This is the PDA authority for the track
-        #[account(
-            seeds = [
-                b"track".as_ref(), 
-                authority.key().as_ref(), 
-                track_id.to_le_bytes().as_ref()
-                ],
-            bump = track.bump
-        )]
-        pub track_authority: UncheckedAccount<'info>,
+            claimed_total = claimed_total.checked_add(amount).ok_or(ErrorCode::MathError)?;
 
-        pub authority: Signer<'info>,
+            emit!(EarningsClaimed {
+                track_id: track.track_id,
+                track: track.key(),
+                contributor,
+                mint: mint_key,
+                amount,
+            });
+        }
 
-        pub token_program: Program<'info, Token>,
+        if let Some(profile) = &mut ctx.accounts.profile {
+            add_mint_total(&mut profile.earnings, &mint_key, claimed_total)?;
+            emit!(ProfileEarningsCredited {
+                wallet: contributor,
+                mint: mint_key,
+                amount: claimed_total,
+            });
+        }
+
+        Ok(())
     }
 
+    pub fn add_accepted_mint(ctx: Context<AddAcceptedMint>, track_id: u64) -> Result<()> {
 
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
 
-    #[event]
-    pub struct EscrowDeposited {
-        pub track_id: u64,
-        pub depositor: Pubkey,
-        pub amount: u64,
-        pub mint: Pubkey,
-    }
+        let registry = &mut ctx.accounts.mint_registry;
+        let mint = ctx.accounts.mint.key();
+        let escrow = ctx.accounts.escrow_token_account.key();
 
-    #[derive(Accounts)]
-    #[instruction(amount: u64, track_id: u64, authority: Pubkey)]
-    pub struct EscrowDeposit<'info> {
-        #[account(mut)]
-        pub payer: Signer<'info>,
+        require!(registry.find(&mint).is_none(), ErrorCode::MintAlreadyAccepted);
+        require!(registry.mints.len() < MAX_ACCEPTED_MINTS, ErrorCode::TooManyAcceptedMints);
+
+        registry.track = ctx.accounts.track.key();
+        registry.bump = ctx.bumps.mint_registry;
+        registry.mints.push(AcceptedMint {
+            mint,
+            escrow,
+            held: 0,
+            credited_outstanding: 0,
+            total_deposited: 0,
+            total_distributed: 0,
+            total_protocol_fees: 0,
+            dust_outstanding: 0,
+        });
+
+        emit!(AcceptedMintAdded {
+            track_id,
+            mint,
+            escrow,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_accepted_mint(ctx: Context<RemoveAcceptedMint>, track_id: u64) -> Result<()> {
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let escrow = &ctx.accounts.escrow_token_account;
+        let registry = &mut ctx.accounts.mint_registry;
+
+        let position = registry.mints
+            .iter()
+            .position(|entry| entry.mint == escrow.mint && entry.escrow == escrow.key())
+            .ok_or(ErrorCode::MintNotAccepted)?;
+
+        // Removing a mint with a balance would strand those funds outside distribute-all
+        require!(escrow.amount == 0, ErrorCode::EscrowNotEmpty);
+
+        registry.mints.remove(position);
+
+        emit!(AcceptedMintRemoved {
+            track_id,
+            mint: escrow.mint,
+        });
+
+        Ok(())
+    }
+
+    pub fn escrow_distribute_all<'info>(ctx: Context<'_, '_, 'info, 'info, EscrowDistributeAll<'info>>, track_id: u64) -> Result<()> {
+
+        // remaining_accounts must hold every registered escrow and its mint, plus the
+        // contributor and fee recipient ATAs for each of those mints and the contributors'
+        // earnings records. Each escrow is drained of everything not held by an open deposit window
+        // or owed to claimants.
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let entries = ctx.accounts.mint_registry.mints.clone();
+
+        for entry in entries.iter() {
+            let escrow_account_info = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == entry.escrow)
+                .ok_or(ErrorCode::MissingEscrowAccount)?;
+
+            let mint_account_info = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == entry.mint)
+                .ok_or(ErrorCode::MissingEscrowAccount)?;
+
+            let escrow = InterfaceAccount::<TokenAccount>::try_from(escrow_account_info)?;
+            require!(escrow.owner == ctx.accounts.track.key(), ErrorCode::InvalidTokenAccountOwner);
+            require!(escrow.mint == entry.mint, ErrorCode::MintNotAccepted);
+
+            let available = entry.available(escrow.amount);
+            if available == 0 {
+                continue;
+            }
+
+            let mint = InterfaceAccount::<Mint>::try_from(mint_account_info)?;
+
+            // Registered mints can live under either token program
+            let token_program_info = if *escrow_account_info.owner == ctx.accounts.token_2022_program.key() {
+                ctx.accounts.token_2022_program.to_account_info()
+            } else {
+                ctx.accounts.token_program.to_account_info()
+            };
+
+            let source = EscrowSource {
+                track: &ctx.accounts.track,
+                escrow: escrow_account_info,
+                mint: mint_account_info,
+                decimals: mint.decimals,
+                token_program: &token_program_info,
+            };
+
+            let earnings_payer = EarningsPayer {
+                payer: &ctx.accounts.authority.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            };
+
+            let settlement = settle_escrow(
+                &source,
+                Payout::Transfer,
+                &ctx.accounts.protocol_config,
+                ctx.accounts.fee_exemption.is_some(),
+                &earnings_payer,
+                ctx.remaining_accounts,
+                available,
+            )?;
+
+            // Everything available was swept, so earlier rounding dust went out with it
+            let registry_entry = ctx.accounts.mint_registry
+                .find_mut(&entry.mint)
+                .ok_or(ErrorCode::MintNotAccepted)?;
+            registry_entry.record_settlement(&settlement)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn start_distribution_run(ctx: Context<StartDistributionRun>, tracks: Vec<Pubkey>) -> Result<()> {
+
+        // Starting a new run replaces whatever is left of the previous one
+        require!(!tracks.is_empty(), ErrorCode::InvalidArgs);
+        require!(tracks.len() <= MAX_RUN_TRACKS, ErrorCode::TooManyRunTracks);
+
+        let run = &mut ctx.accounts.distribution_run;
+        run.authority = ctx.accounts.authority.key();
+        run.mint = ctx.accounts.mint.key();
+        run.tracks = tracks;
+        run.cursor = 0;
+        run.bump = ctx.bumps.distribution_run;
+
+        emit!(DistributionRunStarted {
+            authority: run.authority,
+            mint: run.mint,
+            total: run.tracks.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    pub fn continue_distribution_run<'info>(ctx: Context<'_, '_, 'info, 'info, ContinueDistributionRun<'info>>) -> Result<()> {
+
+        // Works through the run's tracks from the cursor, distributing each escrow's available
+        // balance for the run's mint. remaining_accounts holds each track with its mint registry,
+        // escrow, contributor ATAs and earnings records, plus the fee recipient ATA and any fee
+        // exemptions. Tracks that never set up a registry are skipped by passing the empty registry
+        // PDA. The run stops at the first track whose accounts are missing or when compute runs
+        // low, and the next call resumes from there. Paused tracks are moved to the back of the
+        // run so they are settled once unpaused.
+
+        require!(!ctx.accounts.distribution_run.is_complete(), ErrorCode::DistributionRunComplete);
+
+        let authority = ctx.accounts.authority.key();
+        let earnings_payer = EarningsPayer {
+            payer: &ctx.accounts.authority.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        };
+
+        let run = &mut ctx.accounts.distribution_run;
+        let mut processed: u32 = 0;
+        let mut deferred: usize = 0;
+
+        while !run.is_complete() {
+            if anchor_lang::solana_program::compute_units::sol_remaining_compute_units() < RUN_TRACK_COMPUTE_RESERVE {
+                break;
+            }
+
+            let cursor = run.cursor as usize;
+            let track_key = run.tracks[cursor];
+            let Some(track_info) = ctx.remaining_accounts.iter().find(|acc| acc.key() == track_key) else {
+                break;
+            };
+
+            let track = Account::<Track>::try_from(track_info)?;
+            require_keys_eq!(track.authority, authority, ErrorCode::Unauthorized);
+
+            // Paused tracks go to the back rather than holding up the rest of the catalog,
+            // until only paused tracks are left
+            if track.paused {
+                if deferred + 1 >= run.tracks.len() - cursor {
+                    break;
+                }
+                run.tracks[cursor..].rotate_left(1);
+                deferred += 1;
+                continue;
+            }
+
+            let settled = distribute_run_track(
+                &track,
+                &ctx.accounts.mint,
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.protocol_config,
+                &earnings_payer,
+                ctx.remaining_accounts,
+            )?;
+            if !settled {
+                break;
+            }
+
+            run.cursor += 1;
+            processed += 1;
+            deferred = 0;
+        }
+
+        require!(processed > 0, ErrorCode::DistributionRunStalled);
+
+        emit!(DistributionRunProgress {
+            authority,
+            mint: run.mint,
+            processed,
+            cursor: run.cursor,
+            total: run.tracks.len() as u32,
+        });
+
+        Ok(())
+    }
+
+    pub fn create_sol_vault(ctx: Context<CreateSolVault>, track_id: u64, authority: Pubkey) -> Result<()> {
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(ctx.accounts.track.authority == authority, ErrorCode::InvalidArgs);
+
+        let vault = &mut ctx.accounts.sol_vault;
+        vault.track = ctx.accounts.track.key();
+        vault.bump = ctx.bumps.sol_vault;
+        vault.total_deposited = 0;
+        vault.total_distributed = 0;
+        vault.total_protocol_fees = 0;
+        vault.dust_outstanding = 0;
+
+        Ok(())
+    }
+
+    pub fn sol_deposit(ctx: Context<SolDeposit>, amount: u64, track_id: u64, authority: Pubkey) -> Result<()> {
+
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(ctx.accounts.track.authority == authority, ErrorCode::InvalidArgs);
+
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: ctx.accounts.sol_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::transfer(cpi_ctx, amount)?;
+
+        let vault = &mut ctx.accounts.sol_vault;
+        vault.total_deposited = vault.total_deposited.checked_add(amount).ok_or(ErrorCode::MathError)?;
+
+        emit!(SolDeposited {
+            track_id,
+            depositor: ctx.accounts.payer.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn sol_distribute<'info>(ctx: Context<'_, '_, '_, 'info, SolDistribute<'info>>, amount: u64, track_id: u64) -> Result<()> {
+
+        // Contributor wallets, their earnings records, the native mint fee recipient and, for
+        // catalog tracks, the catalog and its owner are passed as writable remaining_accounts.
+        // Stem royalty positions also need the stem account and the holder's NFT token account.
+        // The vault is program owned so lamports are moved directly, never below rent exemption.
+
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let track = &ctx.accounts.track;
+        let total_bps: u64 = track.payees().iter().map(|(_, bps)| *bps as u64).sum();
+        require!(total_bps == 10000, ErrorCode::InvalidShareTotal);
+
+        let vault_info = ctx.accounts.sol_vault.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+        let available = vault_info.lamports()
+            .saturating_sub(rent_exempt)
+            .saturating_sub(ctx.accounts.sol_vault.credited_outstanding);
+        require!(amount <= available, ErrorCode::InsufficientVaultBalance);
+
+        let config = &ctx.accounts.protocol_config;
+        let mut protocol_fee = 0;
+        if let Some(recipient) = config.recipient_for(&NATIVE_MINT) {
+            if ctx.accounts.fee_exemption.is_none() {
+                protocol_fee = bps_of(amount, config.fee_bps)?;
+            }
+
+            if protocol_fee > 0 {
+                let fee_account = ctx.remaining_accounts
+                    .iter()
+                    .find(|acc| acc.key() == recipient)
+                    .ok_or(ErrorCode::MissingFeeAccount)?;
+
+                vault_info.sub_lamports(protocol_fee)?;
+                fee_account.add_lamports(protocol_fee)?;
+
+                emit!(ProtocolFeeCollected {
+                    track_id,
+                    mint: NATIVE_MINT,
+                    amount: protocol_fee,
+                    recipient,
+                });
+            }
+        }
+
+        let mut net_amount = amount - protocol_fee;
+        let mut label_cut = 0;
+        if let Some(cut) = catalog_cut(track, ctx.remaining_accounts, net_amount)? {
+            let recipient_account = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == cut.recipient)
+                .ok_or(ErrorCode::MissingCatalogAccount)?;
+
+            vault_info.sub_lamports(cut.amount)?;
+            recipient_account.add_lamports(cut.amount)?;
+
+            emit!(LabelCutCollected {
+                track_id,
+                catalog: cut.catalog,
+                mint: NATIVE_MINT,
+                amount: cut.amount,
+                recipient: cut.recipient,
+            });
+
+            label_cut = cut.amount;
+            net_amount -= cut.amount;
+        }
+
+        let earnings_payer = EarningsPayer {
+            payer: &ctx.accounts.authority.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        };
+        let mut paid: u64 = 0;
+        let mut credited: u64 = 0;
+        let min_account_balance = Rent::get()?.minimum_balance(0);
+
+        for (contributor, share_bps) in track.payees() {
+            let share_amount = bps_of(net_amount, share_bps)?;
+
+            if share_amount == 0 {
+                continue;
+            }
+
+            let recipient = if track.is_royalty_stem(&contributor) {
+                stem_holder(&contributor, ctx.remaining_accounts)?
+            } else {
+                contributor
+            };
+
+            let to_account = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == recipient)
+                .ok_or(ErrorCode::InvalidArgs)?;
+
+            // An unfunded wallet can't be sent less than rent exemption, so the share stays in
+            // the vault as a claimable balance instead of failing the whole distribution. A stem
+            // royalty's share is credited to the stem, claimable by its holder via claim_stem_royalty_sol
+            if to_account.lamports() == 0 && share_amount < min_account_balance {
+                credit_earnings(&track.key(), &contributor, &NATIVE_MINT, EarningsCredit::Claimable(share_amount), &earnings_payer, ctx.remaining_accounts)?;
+                credited = credited.checked_add(share_amount).ok_or(ErrorCode::MathError)?;
+                continue;
+            }
+
+            vault_info.sub_lamports(share_amount)?;
+            to_account.add_lamports(share_amount)?;
+
+            if recipient != contributor {
+                emit!(StemRoyaltyPaid {
+                    track: track.key(),
+                    stem: contributor,
+                    holder: recipient,
+                    mint: NATIVE_MINT,
+                    amount: share_amount,
+                });
+            }
+
+            credit_earnings(&track.key(), &contributor, &NATIVE_MINT, EarningsCredit::Paid(share_amount), &earnings_payer, ctx.remaining_accounts)?;
+            paid = paid.checked_add(share_amount).ok_or(ErrorCode::MathError)?;
+        }
+
+        let vault = &mut ctx.accounts.sol_vault;
+        vault.record_settlement(&Settlement {
+            protocol_fee,
+            label_cut,
+            paid,
+            credited,
+            dust: net_amount - paid - credited,
+        })?;
+
+        emit!(SolDistributed {
+            track_id,
+            amount,
+            protocol_fee,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_sol_earnings(ctx: Context<ClaimSolEarnings>, track_id: u64) -> Result<()> {
+
+        // Pays out SOL shares that sol_distribute held back for an unfunded wallet. Open while
+        // paused, like claim_earnings.
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let earnings_entry = ctx.accounts.earnings.entry_mut(&NATIVE_MINT)?;
+        let amount = earnings_entry.claimable;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        earnings_entry.claimable = 0;
+        earnings_entry.paid = earnings_entry.paid.checked_add(amount).ok_or(ErrorCode::MathError)?;
+
+        let vault = &mut ctx.accounts.sol_vault;
+        vault.credited_outstanding = vault.credited_outstanding.checked_sub(amount).ok_or(ErrorCode::MathError)?;
+        vault.total_distributed = vault.total_distributed.checked_add(amount).ok_or(ErrorCode::MathError)?;
+
+        vault.sub_lamports(amount)?;
+        ctx.accounts.contributor.add_lamports(amount)?;
+
+        if let Some(profile) = &mut ctx.accounts.profile {
+            add_mint_total(&mut profile.earnings, &NATIVE_MINT, amount)?;
+            emit!(ProfileEarningsCredited {
+                wallet: ctx.accounts.contributor.key(),
+                mint: NATIVE_MINT,
+                amount,
+            });
+        }
+
+        emit!(EarningsClaimed {
+            track_id,
+            track: ctx.accounts.track.key(),
+            contributor: ctx.accounts.contributor.key(),
+            mint: NATIVE_MINT,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn initialize_protocol_config(ctx: Context<InitializeProtocolConfig>, fee_bps: u16, max_fee_bps: u16) -> Result<()> {
+
+        require!(max_fee_bps <= 10000, ErrorCode::InvalidFeeBps);
+        require!(fee_bps <= max_fee_bps, ErrorCode::FeeAboveMax);
+
+        let config = &mut ctx.accounts.protocol_config;
+        config.admin = ctx.accounts.admin.key();
+        config.fee_bps = fee_bps;
+        config.max_fee_bps = max_fee_bps;
+        config.fee_recipients = Vec::new();
+        config.bump = ctx.bumps.protocol_config;
+        config.guardian = ctx.accounts.admin.key();
+        config.paused = false;
+
+        emit!(ProtocolFeeUpdated {
+            fee_bps,
+            max_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_protocol_fee(ctx: Context<UpdateProtocolConfig>, fee_bps: u16) -> Result<()> {
+
+        let config = &mut ctx.accounts.protocol_config;
+        require!(fee_bps <= config.max_fee_bps, ErrorCode::FeeAboveMax);
+
+        config.fee_bps = fee_bps;
+
+        emit!(ProtocolFeeUpdated {
+            fee_bps,
+            max_fee_bps: config.max_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_fee_recipient(ctx: Context<UpdateProtocolConfig>, mint: Pubkey, recipient: Pubkey) -> Result<()> {
+
+        let config = &mut ctx.accounts.protocol_config;
+
+        match config.fee_recipients.iter_mut().find(|entry| entry.mint == mint) {
+            Some(entry) => entry.recipient = recipient,
+            None => {
+                require!(config.fee_recipients.len() < MAX_FEE_RECIPIENTS, ErrorCode::TooManyFeeRecipients);
+                config.fee_recipients.push(FeeRecipient { mint, recipient });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_fee_exemption(ctx: Context<AddFeeExemption>) -> Result<()> {
+
+        let exemption = &mut ctx.accounts.fee_exemption;
+        exemption.track = ctx.accounts.track.key();
+        exemption.bump = ctx.bumps.fee_exemption;
+
+        emit!(FeeExemptionUpdated {
+            track: exemption.track,
+            exempt: true,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_fee_exemption(ctx: Context<RemoveFeeExemption>) -> Result<()> {
+
+        emit!(FeeExemptionUpdated {
+            track: ctx.accounts.fee_exemption.track,
+            exempt: false,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_guardian(ctx: Context<UpdateProtocolConfig>, guardian: Pubkey) -> Result<()> {
+
+        ctx.accounts.protocol_config.guardian = guardian;
+
+        Ok(())
+    }
+
+    pub fn set_global_pause(ctx: Context<SetGlobalPause>, paused: bool) -> Result<()> {
+
+        ctx.accounts.protocol_config.paused = paused;
+
+        emit!(PauseUpdated {
+            track: None,
+            paused,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_track_pause(ctx: Context<SetTrackPause>, track_id: u64, paused: bool) -> Result<()> {
+
+        let track = &mut ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        track.paused = paused;
+
+        emit!(PauseUpdated {
+            track: Some(track.key()),
+            paused,
+        });
+
+        Ok(())
+    }
+
+    pub fn create_profile(ctx: Context<CreateProfile>, display_name: Option<String>, profile_cid: Option<String>) -> Result<()> {
+
+        let profile = &mut ctx.accounts.profile;
+        profile.wallet = ctx.accounts.wallet.key();
+        profile.display_name = String::new();
+        profile.profile_cid = String::new();
+        profile.tracks = Vec::new();
+        profile.earnings = Vec::new();
+        profile.bump = ctx.bumps.profile;
+        profile.set_details(display_name, profile_cid)?;
+
+        emit!(ProfileUpdated {
+            wallet: profile.wallet,
+            display_name: profile.display_name.clone(),
+            profile_cid: profile.profile_cid.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn update_profile(ctx: Context<UpdateProfile>, display_name: Option<String>, profile_cid: Option<String>) -> Result<()> {
+
+        let profile = &mut ctx.accounts.profile;
+        profile.set_details(display_name, profile_cid)?;
+
+        emit!(ProfileUpdated {
+            wallet: profile.wallet,
+            display_name: profile.display_name.clone(),
+            profile_cid: profile.profile_cid.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn close_track<'info>(ctx: Context<'_, '_, 'info, 'info, CloseTrack<'info>>, track_id: u64) -> Result<()> {
+
+        // Every registered escrow must be passed in remaining_accounts and hold nothing beyond
+        // rounding dust; escrow_distribute_all sweeps whatever is not held or owed to claimants.
+        // Leftover dust goes to the first contributor, so their token account (and the mint)
+        // or wallet must be passed as well. The mint registry and SOL vault PDAs are always
+        // passed so an existing one cannot be left out. The escrows, mint registry, SOL vault
+        // and track are closed and their rent goes to the authority. The track id is not handed
+        // out again by the artist counter.
+
+        let track = &ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(track.catalog.is_none(), ErrorCode::TrackInCatalog);
+
+        let track_id_in_bytes = track.track_id.to_le_bytes();
+        let seeds = &[
+            b"track".as_ref(),
+            track.authority.as_ref(),
+            &track_id_in_bytes,
+            &[track.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let dust_recipient = track.contributors[0];
+
+        let registry_info = ctx.accounts.mint_registry.to_account_info();
+        let entries = if registry_info.data_is_empty() {
+            Vec::new()
+        } else {
+            require_keys_eq!(*registry_info.owner, crate::ID, ErrorCode::InvalidArgs);
+            MintRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?.mints
+        };
+
+        for entry in entries.iter() {
+            // Held deposits and unclaimed earnings still have a claim on the escrow
+            require!(entry.held == 0 && entry.credited_outstanding == 0, ErrorCode::OutstandingBalances);
+
+            let escrow_account_info = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == entry.escrow)
+                .ok_or(ErrorCode::MissingEscrowAccount)?;
+
+            let escrow = InterfaceAccount::<TokenAccount>::try_from(escrow_account_info)?;
+            require!(escrow.owner == track.key(), ErrorCode::InvalidTokenAccountOwner);
+            require!(escrow.amount <= entry.dust_outstanding, ErrorCode::EscrowNotEmpty);
+
+            let token_program_info = if *escrow_account_info.owner == ctx.accounts.token_2022_program.key() {
+                ctx.accounts.token_2022_program.to_account_info()
+            } else {
+                ctx.accounts.token_program.to_account_info()
+            };
+
+            if escrow.amount > 0 {
+                let mint_info = ctx.remaining_accounts
+                    .iter()
+                    .find(|acc| acc.key() == entry.mint)
+                    .ok_or(ErrorCode::InvalidArgs)?;
+                let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+                let source = EscrowSource {
+                    track,
+                    escrow: escrow_account_info,
+                    mint: mint_info,
+                    decimals: mint.decimals,
+                    token_program: &token_program_info,
+                };
+                let recipient_token_account = source.ata_for(&dust_recipient);
+                let to_account = ctx.remaining_accounts
+                    .iter()
+                    .find(|acc| acc.key() == recipient_token_account)
+                    .ok_or(ErrorCode::InvalidArgs)?;
+                source.transfer(to_account, escrow.amount)?;
+
+                emit!(DustSwept {
+                    track: track.key(),
+                    mint: entry.mint,
+                    recipient: dust_recipient,
+                    amount: escrow.amount,
+                });
+            }
+
+            let cpi_accounts = CloseAccount {
+                account: escrow_account_info.clone(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: track.to_account_info(),
+            };
+            close_account(CpiContext::new_with_signer(token_program_info, cpi_accounts, signer))?;
+        }
+
+        if !registry_info.data_is_empty() {
+            close_program_account(&registry_info, &ctx.accounts.authority.to_account_info())?;
+        }
+
+        let vault_info = ctx.accounts.sol_vault.to_account_info();
+        if !vault_info.data_is_empty() {
+            require_keys_eq!(*vault_info.owner, crate::ID, ErrorCode::InvalidArgs);
+            let vault = SolVault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+            require!(vault.credited_outstanding == 0, ErrorCode::OutstandingBalances);
+
+            let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+            let surplus = vault_info.lamports().saturating_sub(rent_exempt);
+            require!(surplus <= vault.dust_outstanding, ErrorCode::EscrowNotEmpty);
+
+            if surplus > 0 {
+                let authority_info = ctx.accounts.authority.to_account_info();
+                let to_account = if dust_recipient == authority_info.key() {
+                    &authority_info
+                } else {
+                    ctx.remaining_accounts
+                        .iter()
+                        .find(|acc| acc.key() == dust_recipient)
+                        .ok_or(ErrorCode::InvalidArgs)?
+                };
+                vault_info.sub_lamports(surplus)?;
+                to_account.add_lamports(surplus)?;
+
+                emit!(DustSwept {
+                    track: track.key(),
+                    mint: NATIVE_MINT,
+                    recipient: dust_recipient,
+                    amount: surplus,
+                });
+            }
+
+            close_program_account(&vault_info, &ctx.accounts.authority.to_account_info())?;
+        }
+
+        emit!(TrackClosed {
+            track_id,
+            authority: track.authority,
+            escrows_closed: entries.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    pub fn resize_track(ctx: Context<ResizeTrack>, track_id: u64) -> Result<()> {
+
+        // The realloc constraint already did the work; this only checks the id
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        Ok(())
+    }
+
+    pub fn create_catalog(ctx: Context<CreateCatalog>, catalog_id: u64, name: String, label_share_bps: u16) -> Result<()> {
+
+        require!(name.len() <= MAX_TITLE_LEN, ErrorCode::TitleTooLong);
+        require!(label_share_bps <= 10000, ErrorCode::InvalidLabelShare);
+
+        let catalog = &mut ctx.accounts.catalog;
+        catalog.creator = ctx.accounts.owner.key();
+        catalog.catalog_id = catalog_id;
+        catalog.owner = ctx.accounts.owner.key();
+        catalog.admins = Vec::new();
+        catalog.name = name;
+        catalog.label_share_bps = label_share_bps;
+        catalog.track_count = 0;
+        catalog.bump = ctx.bumps.catalog;
+
+        emit!(CatalogCreated {
+            catalog: catalog.key(),
+            owner: catalog.owner,
+            label_share_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_catalog_admin(ctx: Context<ManageCatalog>, admin: Pubkey) -> Result<()> {
+
+        let catalog = &mut ctx.accounts.catalog;
+        if !catalog.admins.contains(&admin) {
+            require!(catalog.admins.len() < MAX_CATALOG_ADMINS, ErrorCode::TooManyCatalogAdmins);
+            catalog.admins.push(admin);
+        }
+
+        emit!(CatalogAdminsUpdated {
+            catalog: catalog.key(),
+            admins: catalog.admins.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_catalog_admin(ctx: Context<ManageCatalog>, admin: Pubkey) -> Result<()> {
+
+        let catalog = &mut ctx.accounts.catalog;
+        catalog.admins.retain(|existing| *existing != admin);
+
+        emit!(CatalogAdminsUpdated {
+            catalog: catalog.key(),
+            admins: catalog.admins.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn set_catalog_split(ctx: Context<ManageCatalog>, label_share_bps: u16) -> Result<()> {
+
+        require!(label_share_bps <= 10000, ErrorCode::InvalidLabelShare);
+
+        let catalog = &mut ctx.accounts.catalog;
+        catalog.label_share_bps = label_share_bps;
+
+        emit!(CatalogSplitUpdated {
+            catalog: catalog.key(),
+            label_share_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn transfer_catalog(ctx: Context<ManageCatalog>, new_owner: Pubkey) -> Result<()> {
+
+        // Member tracks point at the catalog, not its owner, so they all move with it.
+        // The label cut follows the new owner and the old admin list is dropped.
+
+        let catalog = &mut ctx.accounts.catalog;
+        let previous_owner = catalog.owner;
+        catalog.owner = new_owner;
+        catalog.admins.clear();
+
+        emit!(CatalogTransferred {
+            catalog: catalog.key(),
+            previous_owner,
+            new_owner,
+            track_count: catalog.track_count,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_track_to_catalog(ctx: Context<AddTrackToCatalog>, track_id: u64) -> Result<()> {
+
+        // Needs both the track authority and a catalog admin, since the label cut comes out of the track's payouts
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let catalog = &mut ctx.accounts.catalog;
+        require!(catalog.is_admin(&ctx.accounts.admin.key()), ErrorCode::Unauthorized);
+
+        let track = &mut ctx.accounts.track;
+        require!(track.catalog.is_none(), ErrorCode::TrackAlreadyInCatalog);
+
+        // The track only ever agreed to the split in force when it joined; later raises do not apply to it
+        track.catalog = Some(catalog.key());
+        track.catalog_share_cap = catalog.label_share_bps;
+        catalog.track_count = catalog.track_count.checked_add(1).ok_or(ErrorCode::MathError)?;
+
+        emit!(CatalogTrackAdded {
+            catalog: catalog.key(),
+            track: track.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_track_from_catalog(ctx: Context<RemoveTrackFromCatalog>, track_id: u64) -> Result<()> {
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let catalog = &mut ctx.accounts.catalog;
+        require!(catalog.is_admin(&ctx.accounts.admin.key()), ErrorCode::Unauthorized);
+
+        let track = &mut ctx.accounts.track;
+        track.catalog = None;
+        track.catalog_share_cap = 0;
+        catalog.track_count = catalog.track_count.checked_sub(1).ok_or(ErrorCode::MathError)?;
+
+        emit!(CatalogTrackRemoved {
+            catalog: catalog.key(),
+            track: track.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn leave_catalog(ctx: Context<LeaveCatalog>, track_id: u64) -> Result<()> {
+
+        // The track authority can take its track out without the label's consent
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let catalog = &mut ctx.accounts.catalog;
+        let track = &mut ctx.accounts.track;
+        track.catalog = None;
+        track.catalog_share_cap = 0;
+        catalog.track_count = catalog.track_count.checked_sub(1).ok_or(ErrorCode::MathError)?;
+
+        emit!(CatalogTrackRemoved {
+            catalog: catalog.key(),
+            track: track.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn create_release(
+        ctx: Context<CreateRelease>,
+        release_id: u64,
+        title: String,
+        upc: String,
+        release_date: i64,
+        cover_cid: String,
+        tracks: Vec<ReleaseTrack>,
+    ) -> Result<()> {
+
+        let release = &mut ctx.accounts.release;
+        release.authority = ctx.accounts.authority.key();
+        release.release_id = release_id;
+        release.title = String::new();
+        release.upc = String::new();
+        release.cover_cid = String::new();
+        release.release_date = release_date;
+        release.tracks = Vec::new();
+        release.bump = ctx.bumps.release;
+
+        release.set_details(Some(title), Some(upc), Some(cover_cid))?;
+        verify_release_tracks(&tracks, ctx.remaining_accounts)?;
+        release.set_tracks(tracks)?;
+
+        emit!(ReleaseUpdated {
+            release: release.key(),
+            upc: release.upc.clone(),
+            release_date,
+            tracks: release.tracks.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn update_release(
+        ctx: Context<UpdateRelease>,
+        release_id: u64,
+        title: Option<String>,
+        upc: Option<String>,
+        release_date: Option<i64>,
+        cover_cid: Option<String>,
+        tracks: Option<Vec<ReleaseTrack>>,
+    ) -> Result<()> {
+
+        let release = &mut ctx.accounts.release;
+        require!(release.release_id == release_id, ErrorCode::InvalidArgs);
+
+        release.set_details(title, upc, cover_cid)?;
+        if let Some(release_date) = release_date {
+            release.release_date = release_date;
+        }
+
+        if let Some(tracks) = tracks {
+            verify_release_tracks(&tracks, ctx.remaining_accounts)?;
+            release.set_tracks(tracks)?;
+        }
+
+        emit!(ReleaseUpdated {
+            release: release.key(),
+            upc: release.upc.clone(),
+            release_date: release.release_date,
+            tracks: release.tracks.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit_to_release<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DepositToRelease<'info>>,
+        amount: u64,
+        reference: Option<Pubkey>,
+        memo: Option<String>,
+    ) -> Result<()> {
+
+        // Splits a release-level payment across member tracks by weight. Each portion lands in
+        // that track's escrow and goes through the track's own split when it is distributed.
+        // remaining_accounts holds every member track with its mint registry and escrow. With a
+        // reference or memo, each portion also gets a ledger entry like escrow_deposit writes, so
+        // the deposit PDA at each track's next deposit_count goes in remaining_accounts as well.
+
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        if let Some(memo) = &memo {
+            require!(memo.len() <= MAX_MEMO_LEN, ErrorCode::MemoTooLong);
+        }
+
+        let release = &ctx.accounts.release;
+        let mint_key = ctx.accounts.mint.key();
+        let depositor = ctx.accounts.payer.key();
+        let mut undivided = amount;
+        let record = reference.is_some() || memo.is_some();
+        let earnings_payer = EarningsPayer {
+            payer: &ctx.accounts.payer.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+        };
+
+        for (i, member) in release.tracks.iter().enumerate() {
+            // The last track takes the rounding remainder so the whole payment is deposited
+            let portion = if i == release.tracks.len() - 1 {
+                undivided
+            } else {
+                bps_of(amount, member.weight_bps)?
+            };
+            undivided = undivided.checked_sub(portion).ok_or(ErrorCode::MathError)?;
+
+            if portion == 0 {
+                continue;
+            }
+
+            let track_info = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == member.track)
+                .ok_or(ErrorCode::MissingReleaseTrackAccount)?;
+            let track = Account::<Track>::try_from(track_info)?;
+            require!(!track.paused, ErrorCode::TrackPaused);
+
+            let (registry_address, _) = Pubkey::find_program_address(
+                &[b"mint_registry".as_ref(), member.track.as_ref()],
+                &crate::ID,
+            );
+            let registry_info = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == registry_address)
+                .ok_or(ErrorCode::MissingReleaseTrackAccount)?;
+            require_keys_eq!(*registry_info.owner, crate::ID, ErrorCode::MissingReleaseTrackAccount);
+
+            let mut registry = MintRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+            let entry = registry.find_mut(&mint_key).ok_or(ErrorCode::MintNotAccepted)?;
+
+            let escrow_info = ctx.remaining_accounts
+                .iter()
+                .find(|acc| acc.key() == entry.escrow)
+                .ok_or(ErrorCode::MissingEscrowAccount)?;
+            let mut escrow = InterfaceAccount::<TokenAccount>::try_from(escrow_info)?;
+
+            let received = transfer_into_escrow(
+                &ctx.accounts.payer_token_account.to_account_info(),
+                &mut escrow,
+                &ctx.accounts.mint,
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                portion,
+            )?;
+
+            entry.total_deposited = entry.total_deposited.checked_add(received).ok_or(ErrorCode::MathError)?;
+
+            let deposit_id = if record {
+                let deposit_id = registry.deposit_count;
+                registry.deposit_count = deposit_id.checked_add(1).ok_or(ErrorCode::MathError)?;
+
+                let id_bytes = deposit_id.to_le_bytes();
+                let (deposit_address, bump) = Pubkey::find_program_address(
+                    &[b"deposit".as_ref(), member.track.as_ref(), &id_bytes],
+                    &crate::ID,
+                );
+                let deposit_info = ctx.remaining_accounts
+                    .iter()
+                    .find(|acc| acc.key() == deposit_address)
+                    .ok_or(ErrorCode::MissingDepositRecord)?;
+                let seeds = &[b"deposit".as_ref(), member.track.as_ref(), &id_bytes, &[bump]];
+                create_pda_account(&earnings_payer, deposit_info, 8 + Deposit::INIT_SPACE, &[&seeds[..]])?;
+
+                let deposit = Deposit {
+                    track: member.track,
+                    depositor,
+                    mint: mint_key,
+                    deposit_id,
+                    amount: received,
+                    release_at: Clock::get()?.unix_timestamp,
+                    status: DepositStatus::Released,
+                    bump,
+                    reference,
+                    memo: memo.clone().unwrap_or_default(),
+                };
+                deposit.try_serialize(&mut &mut deposit_info.try_borrow_mut_data()?[..])?;
+                Some(deposit_id)
+            } else {
+                None
+            };
+            registry.try_serialize(&mut &mut registry_info.try_borrow_mut_data()?[..])?;
+
+            emit!(EscrowDeposited {
+                track_id: track.track_id,
+                depositor,
+                amount: received,
+                mint: mint_key,
+                transfer_fee: portion - received,
+                deposit_id,
+                reference,
+                memo: memo.clone(),
+            });
+        }
+
+        emit!(ReleaseDeposited {
+            release: release.key(),
+            depositor,
+            mint: mint_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn create_track_collection(ctx: Context<CreateTrackCollection>, track_id: u64, uri: String) -> Result<()> {
+
+        // Mints the track's sized collection NFT to the authority. Stem NFTs minted afterwards
+        // are verified members of it.
+
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::CidTooLong);
+
+        let track = &mut ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(track.collection_mint.is_none(), ErrorCode::CollectionAlreadyCreated);
+
+        let track_id_in_bytes = track.track_id.to_le_bytes();
+        let track_authority = track.authority;
+        let seeds = &[
+            b"track".as_ref(),
+            track_authority.as_ref(),
+            &track_id_in_bytes,
+            &[track.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts_mint = MintTo {
+            mint: ctx.accounts.collection_mint.to_account_info(),
+            to: ctx.accounts.authority_token_account.to_account_info(),
+            authority: track.to_account_info(),
+        };
+        mint_to(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts_mint, signer),
+            1,
+        )?;
+
+        let data = DataV2 {
+            name: metadata_name(track.title.clone()),
+            symbol: STEM_NFT_SYMBOL.to_string(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: Some(stem_nft_creators(track)?),
+            collection: None,
+            uses: None,
+        };
+
+        let cpi_accounts_metadata = CreateMetadataAccountsV3 {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            mint: ctx.accounts.collection_mint.to_account_info(),
+            mint_authority: track.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            update_authority: track.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_metadata_accounts_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_metadata, signer),
+            data,
+            true,
+            true,
+            Some(CollectionDetails::V1 { size: 0 }),
+        )?;
+
+        let cpi_accounts_edition = CreateMasterEditionV3 {
+            edition: ctx.accounts.master_edition.to_account_info(),
+            mint: ctx.accounts.collection_mint.to_account_info(),
+            update_authority: track.to_account_info(),
+            mint_authority: track.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_master_edition_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_edition, signer),
+            Some(0),
+        )?;
+
+        let collection_mint = ctx.accounts.collection_mint.key();
+        track.collection_mint = Some(collection_mint);
+
+        emit!(TrackCollectionCreated {
+            track_id,
+            collection_mint,
+        });
+
+        Ok(())
+    }
+
+    pub fn mint_stem_nft(
+        ctx: Context<StemMintNFT>,
+        track_id: u64,
+        stem_index: u32,
+        uri: String,
+        seller_fee_bps: u16,
+    ) -> Result<()> {
+
+        // One NFT per stem. Creators mint their own stems to a wallet of their choice; the track
+        // authority can airdrop to an offline collaborator without their signature, but only to
+        // the stem's creator or a listed contributor.
+
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::CidTooLong);
+        require!(seller_fee_bps <= 10000, ErrorCode::InvalidFeeBps);
+
+        let track = &ctx.accounts.track;
+        let track_authority = track.authority;
+        let track_bump = track.bump;
+        let mint_pubkey = ctx.accounts.mint.key();
+
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let stem = &mut ctx.accounts.stem;
+        require!(stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+        require!(!stem.retired, ErrorCode::StemRetired);
+        require!(stem.mint.is_none(), ErrorCode::StemMintAlreadyLinked);
+
+        let signer_key = ctx.accounts.authority.key();
+        let recipient = ctx.accounts.recipient.key();
+        if signer_key != stem.creator {
+            require!(signer_key == track_authority, ErrorCode::Unauthorized);
+            require!(
+                recipient == stem.creator || track.contributors.contains(&recipient),
+                ErrorCode::NotAContributor
+            );
+        }
+
+        let stem_type = stem.stem_type;
+        stem.mint = Some(mint_pubkey);
+
+        let link = &mut ctx.accounts.stem_mint_link;
+        link.mint = mint_pubkey;
+        link.track = track.key();
+        link.stem_index = stem_index;
+        link.bump = ctx.bumps.stem_mint_link;
+
+        let track_id_in_bytes = track_id.to_le_bytes();
+        let seeds = &[
+            b"track".as_ref(),
+            track_authority.as_ref(),
+            &track_id_in_bytes,
+            &[track_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts_mint = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: track.to_account_info(),
+        };
+
+        let cpi_program_mint = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx_mint = CpiContext::new_with_signer(
+            cpi_program_mint,
+            cpi_accounts_mint,
+            signer,
+        );
+        mint_to(cpi_ctx_mint, 1)?;
+
+        // Metaplex metadata so wallets and marketplaces can show the stem, with the
+        // contributors as (unverified) creators splitting secondary royalties like the track
+        let data = DataV2 {
+            name: stem_nft_name(&track.title, stem_type),
+            symbol: STEM_NFT_SYMBOL.to_string(),
+            uri,
+            seller_fee_basis_points: seller_fee_bps,
+            creators: Some(stem_nft_creators(track)?),
+            collection: track.collection_mint.map(|key| Collection { verified: false, key }),
+            uses: None,
+        };
+
+        let cpi_accounts_metadata = CreateMetadataAccountsV3 {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            mint_authority: track.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            update_authority: track.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_metadata_accounts_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_metadata, signer),
+            data,
+            true,
+            true,
+            None,
+        )?;
+
+        let cpi_accounts_edition = CreateMasterEditionV3 {
+            edition: ctx.accounts.master_edition.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            update_authority: track.to_account_info(),
+            mint_authority: track.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_master_edition_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_edition, signer),
+            Some(0),
+        )?;
+
+        // Stems of a track with a collection are verified members of it, signed by the track PDA
+        if let Some(collection_mint) = track.collection_mint {
+            let (Some(collection_mint_info), Some(collection_metadata), Some(collection_master_edition)) = (
+                &ctx.accounts.collection_mint,
+                &ctx.accounts.collection_metadata,
+                &ctx.accounts.collection_master_edition,
+            ) else {
+                return err!(ErrorCode::MissingCollectionAccount);
+            };
+            require_keys_eq!(collection_mint_info.key(), collection_mint, ErrorCode::MissingCollectionAccount);
+
+            let cpi_accounts_verify = VerifySizedCollectionItem {
+                payer: ctx.accounts.payer.to_account_info(),
+                metadata: ctx.accounts.metadata.to_account_info(),
+                collection_authority: track.to_account_info(),
+                collection_mint: collection_mint_info.to_account_info(),
+                collection_metadata: collection_metadata.to_account_info(),
+                collection_master_edition: collection_master_edition.to_account_info(),
+            };
+            verify_sized_collection_item(
+                CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_verify, signer),
+                None,
+            )?;
+        }
+
+        // The master edition now holds mint authority with no prints allowed, so the stem
+        // stays a 1-of-1. Check it rather than trust the CPI.
+        ctx.accounts.mint.reload()?;
+        require!(ctx.accounts.mint.supply == 1, ErrorCode::InvalidStemMint);
+        require!(
+            ctx.accounts.mint.mint_authority == COption::Some(ctx.accounts.master_edition.key()),
+            ErrorCode::InvalidStemMint
+        );
+
+        emit!(StemNFTMinted {
+            track_id: track.track_id,
+            stem_index,
+            mint: mint_pubkey,
+            recipient: ctx.accounts.recipient_token_account.owner,
+            minted_by: signer_key,
+        });
+
+        Ok(())
+    
+    }
+
+    pub fn verify_stem_in_collection(ctx: Context<VerifyStemInCollection>, track_id: u64, stem_index: u32) -> Result<()> {
+
+        // Stem NFTs minted before the track had a collection are added to it afterwards. The
+        // track PDA is both the stems' update authority and the collection authority.
+
+        let track = &ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+        let collection_mint = track.collection_mint.ok_or(ErrorCode::MissingCollectionAccount)?;
+        require_keys_eq!(ctx.accounts.collection_mint.key(), collection_mint, ErrorCode::MissingCollectionAccount);
+
+        let stem_mint = ctx.accounts.stem.mint.ok_or(ErrorCode::StemNotMinted)?;
+        require_keys_eq!(ctx.accounts.stem_mint.key(), stem_mint, ErrorCode::InvalidArgs);
+
+        let track_id_in_bytes = track.track_id.to_le_bytes();
+        let seeds = &[
+            b"track".as_ref(),
+            track.authority.as_ref(),
+            &track_id_in_bytes,
+            &[track.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = SetAndVerifySizedCollectionItem {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            collection_authority: track.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            update_authority: track.to_account_info(),
+            collection_mint: ctx.accounts.collection_mint.to_account_info(),
+            collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+            collection_master_edition: ctx.accounts.collection_master_edition.to_account_info(),
+        };
+        set_and_verify_sized_collection_item(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts, signer),
+            None,
+        )?;
+
+        emit!(StemCollectionVerified {
+            track_id,
+            stem_index,
+            mint: stem_mint,
+            collection_mint,
+        });
+
+        Ok(())
+    }
+
+    pub fn mint_contributor_badge(
+        ctx: Context<MintContributorBadge>,
+        track_id: u64,
+        role: ContributorRole,
+        uri: String,
+    ) -> Result<()> {
+
+        // Proof of credit for a contributor: a 1-of-1 minted by the track PDA and frozen in the
+        // contributor's wallet before the master edition takes over freeze authority, so it can
+        // never be thawed, transferred or sold
+
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::CidTooLong);
+
+        let track = &ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let contributor = ctx.accounts.contributor.key();
+        require!(track.contributors.contains(&contributor), ErrorCode::NotAContributor);
+
+        let track_id_in_bytes = track_id.to_le_bytes();
+        let track_authority = track.authority;
+        let seeds = &[
+            b"track".as_ref(),
+            track_authority.as_ref(),
+            &track_id_in_bytes,
+            &[track.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts_mint = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.contributor_token_account.to_account_info(),
+            authority: track.to_account_info(),
+        };
+        mint_to(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts_mint, signer),
+            1,
+        )?;
+
+        let cpi_accounts_freeze = FreezeAccount {
+            account: ctx.accounts.contributor_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority: track.to_account_info(),
+        };
+        freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts_freeze,
+            signer,
+        ))?;
+
+        let data = DataV2 {
+            name: metadata_name(format!("{} - {}", track.title, role.label())),
+            symbol: BADGE_SYMBOL.to_string(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        };
+
+        let cpi_accounts_metadata = CreateMetadataAccountsV3 {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            mint_authority: track.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            update_authority: track.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_metadata_accounts_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_metadata, signer),
+            data,
+            false,
+            true,
+            None,
+        )?;
+
+        let cpi_accounts_edition = CreateMasterEditionV3 {
+            edition: ctx.accounts.master_edition.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            update_authority: track.to_account_info(),
+            mint_authority: track.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_master_edition_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts_edition, signer),
+            Some(0),
+        )?;
+
+        let mint = ctx.accounts.mint.key();
+        let badge = &mut ctx.accounts.badge;
+        badge.track = track.key();
+        badge.contributor = contributor;
+        badge.role = role;
+        badge.mint = mint;
+        badge.bump = ctx.bumps.badge;
+
+        emit!(ContributorBadgeMinted {
+            track_id,
+            contributor,
+            role,
+            mint,
+        });
+
+        Ok(())
+    }
+
+    pub fn attach_stem_royalty(ctx: Context<AttachStemRoyalty>, track_id: u64, stem_index: u32, share_bps: u16) -> Result<()> {
+
+        // Opt-in, signed by the stem creator: moves `share_bps` of their split onto the stem
+        // itself. The position is kept in `royalty_stems` and paid to whoever holds the stem
+        // NFT, so it changes hands with the NFT.
+
+        let track = &mut ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let stem = &ctx.accounts.stem;
+        let stem_key = stem.key();
+        require!(stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+        require!(!stem.retired, ErrorCode::StemRetired);
+        let mint = stem.mint.ok_or(ErrorCode::StemNotMinted)?;
+        require!(!track.is_royalty_stem(&stem_key), ErrorCode::StemRoyaltyAlreadyAttached);
+        require!(track.royalty_stems.len() < MAX_CONTRIBUTORS, ErrorCode::TooManyContributors);
+
+        let creator_index = track.contributors
+            .iter()
+            .position(|c| c == &stem.creator)
+            .ok_or(ErrorCode::NotAContributor)?;
+        require!(
+            share_bps > 0 && share_bps <= track.shares[creator_index],
+            ErrorCode::InvalidRoyaltyShare
+        );
+
+        let old_version = track.royalty_version;
+        track.shares[creator_index] -= share_bps;
+        track.royalty_stems.push(StemRoyalty {
+            stem: stem_key,
+            share_bps,
+        });
+        track.royalty_version = old_version.checked_add(1).ok_or(ErrorCode::MathError)?;
+
+        emit!(StemRoyaltyAttached {
+            track_id,
+            stem_index,
+            stem: stem_key,
+            mint,
+            share_bps,
+        });
+
+        emit!(SharesUpdated {
+            track_id,
+            new_shares: track.shares.clone(),
+            old_version,
+            new_version: track.royalty_version,
+        });
+
+        Ok(())
+    }
+
+    pub fn detach_stem_royalty(ctx: Context<DetachStemRoyalty>, track_id: u64, stem_index: u32, recipient: Option<Pubkey>) -> Result<()> {
+
+        // Ends a stem royalty position. The current NFT holder gives it up, so both they and the
+        // track authority sign; the share goes back to the stem creator, or to the contributor the
+        // authority names in `recipient` if update_shares has since removed the creator. Earnings
+        // already credited to the stem stay claimable through claim_stem_royalty.
+
+        let track = &mut ctx.accounts.track;
+        require!(track.track_id == track_id, ErrorCode::InvalidArgs);
+
+        let stem = &ctx.accounts.stem;
+        let stem_key = stem.key();
+        require!(stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+
+        let position = track.royalty_stems
+            .iter()
+            .position(|royalty| royalty.stem == stem_key)
+            .ok_or(ErrorCode::StemRoyaltyNotAttached)?;
+        let receiver = if track.contributors.contains(&stem.creator) {
+            stem.creator
+        } else {
+            recipient.ok_or(ErrorCode::NotAContributor)?
+        };
+        let receiver_index = track.contributors
+            .iter()
+            .position(|c| c == &receiver)
+            .ok_or(ErrorCode::NotAContributor)?;
+
+        let old_version = track.royalty_version;
+        let royalty = track.royalty_stems.remove(position);
+        track.shares[receiver_index] = track.shares[receiver_index]
+            .checked_add(royalty.share_bps)
+            .ok_or(ErrorCode::MathError)?;
+        track.royalty_version = old_version.checked_add(1).ok_or(ErrorCode::MathError)?;
+
+        emit!(StemRoyaltyDetached {
+            track_id,
+            stem_index,
+            stem: stem_key,
+            holder: ctx.accounts.holder.key(),
+            recipient: receiver,
+            share_bps: royalty.share_bps,
+        });
+
+        emit!(SharesUpdated {
+            track_id,
+            new_shares: track.shares.clone(),
+            old_version,
+            new_version: track.royalty_version,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_stem_royalty(ctx: Context<ClaimStemRoyalty>, track_id: u64, stem_index: u32) -> Result<()> {
+
+        // Pays out a stem royalty position's claimable balance to the current NFT holder.
+        // Like claim_earnings this stays open while the protocol or the track is paused.
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(ctx.accounts.stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+
+        let mint_key = ctx.accounts.mint.key();
+        let entry = ctx.accounts.mint_registry
+            .find_mut(&mint_key)
+            .ok_or(ErrorCode::MintNotAccepted)?;
+        require_keys_eq!(ctx.accounts.escrow_token_account.key(), entry.escrow, ErrorCode::MissingEscrowAccount);
+
+        let earnings_entry = ctx.accounts.earnings.entry_mut(&mint_key)?;
+        let amount = earnings_entry.claimable;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        earnings_entry.claimable = 0;
+        earnings_entry.paid = earnings_entry.paid.checked_add(amount).ok_or(ErrorCode::MathError)?;
+        entry.credited_outstanding = entry.credited_outstanding.checked_sub(amount).ok_or(ErrorCode::MathError)?;
+        entry.total_distributed = entry.total_distributed.checked_add(amount).ok_or(ErrorCode::MathError)?;
+
+        let source = EscrowSource {
+            track: &ctx.accounts.track,
+            escrow: &ctx.accounts.escrow_token_account.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            decimals: ctx.accounts.mint.decimals,
+            token_program: &ctx.accounts.token_program.to_account_info(),
+        };
+        source.transfer(&ctx.accounts.holder_token_account.to_account_info(), amount)?;
+
+        let holder = ctx.accounts.holder.key();
+        emit!(StemRoyaltyPaid {
+            track: ctx.accounts.track.key(),
+            stem: ctx.accounts.stem.key(),
+            holder,
+            mint: mint_key,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_stem_royalty_sol(ctx: Context<ClaimStemRoyaltySol>, track_id: u64, stem_index: u32) -> Result<()> {
+
+        // SOL counterpart of claim_stem_royalty: pays out what sol_distribute held back for a
+        // stem whose holder could not receive lamports at the time. Open while paused.
+
+        require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+        require!(ctx.accounts.stem.stem_index == stem_index, ErrorCode::InvalidArgs);
+
+        let earnings_entry = ctx.accounts.earnings.entry_mut(&NATIVE_MINT)?;
+        let amount = earnings_entry.claimable;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        earnings_entry.claimable = 0;
+        earnings_entry.paid = earnings_entry.paid.checked_add(amount).ok_or(ErrorCode::MathError)?;
+
+        let vault = &mut ctx.accounts.sol_vault;
+        vault.credited_outstanding = vault.credited_outstanding.checked_sub(amount).ok_or(ErrorCode::MathError)?;
+        vault.total_distributed = vault.total_distributed.checked_add(amount).ok_or(ErrorCode::MathError)?;
+
+        vault.sub_lamports(amount)?;
+        ctx.accounts.holder.add_lamports(amount)?;
+
+        emit!(StemRoyaltyPaid {
+            track: ctx.accounts.track.key(),
+            stem: ctx.accounts.stem.key(),
+            holder: ctx.accounts.holder.key(),
+            mint: NATIVE_MINT,
+            amount,
+        });
+
+        Ok(())
+    }
+
+
+
+}
+
+// Token accounts and program needed to move tokens out of a track escrow,
+// signing as the track PDA
+struct EscrowSource<'a, 'info> {
+    track: &'a Account<'info, Track>,
+    escrow: &'a AccountInfo<'info>,
+    mint: &'a AccountInfo<'info>,
+    decimals: u8,
+    token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> EscrowSource<'a, 'info> {
+    fn ata_for(&self, owner: &Pubkey) -> Pubkey {
+        anchor_spl::associated_token::get_associated_token_address_with_program_id(
+            owner,
+            self.mint.key,
+            self.token_program.key,
+        )
+    }
+
+    fn transfer(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let track_id_in_bytes = self.track.track_id.to_le_bytes();
+        let seeds = &[
+            b"track".as_ref(),
+            self.track.authority.as_ref(),
+            &track_id_in_bytes,
+            &[self.track.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = TransferChecked {
+            from: self.escrow.clone(),
+            mint: self.mint.clone(),
+            to: to.clone(),
+            authority: self.track.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.clone(), cpi_accounts, signer);
+        transfer_checked(cpi_ctx, amount, self.decimals)
+    }
+}
+
+// Moves `amount` from a depositor into a track escrow and returns what actually landed.
+// Token-2022 transfer-fee mints withhold part of the transfer, so this can be less than `amount`.
+fn transfer_into_escrow<'info>(
+    from: &AccountInfo<'info>,
+    escrow: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<u64> {
+
+    let balance_before = escrow.amount;
+
+    let cpi_accounts = TransferChecked {
+        from: from.clone(),
+        mint: mint.to_account_info(),
+        to: escrow.to_account_info(),
+        authority: authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.clone(), cpi_accounts);
+    transfer_checked(cpi_ctx, amount, mint.decimals)?;
+
+    escrow.reload()?;
+    Ok(escrow.amount
+        .checked_sub(balance_before)
+        .ok_or(ErrorCode::MathError)?)
+}
+
+// bps share of an amount, rounded down
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    Ok(amount
+        .checked_mul(bps as u64)
+        .ok_or(ErrorCode::MathError)?
+        / 10000)
+}
+
+// Shared body of escrow_distribute and escrow_credit
+fn distribute_escrow<'info>(ctx: Context<'_, '_, '_, 'info, EscrowDistribute<'info>>, amount: u64, track_id: u64, payout: Payout) -> Result<()> {
+
+    //whenever you are reading from multiple accounts in a loop you have to clone the data you need first to avoid borrow checker issues
+    // trust me i tried for 2 days
+
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(ctx.accounts.track.track_id == track_id, ErrorCode::InvalidArgs);
+    require!(ctx.accounts.escrow_token_account.owner == ctx.accounts.track.key(), ErrorCode::InvalidTokenAccountOwner);
+
+    let available = ctx.accounts.mint_registry
+        .find(&ctx.accounts.mint.key())
+        .ok_or(ErrorCode::MintNotAccepted)?
+        .available(ctx.accounts.escrow_token_account.amount);
+    require!(amount <= available, ErrorCode::InsufficientEscrowBalance);
+
+    let source = EscrowSource {
+        track: &ctx.accounts.track,
+        escrow: &ctx.accounts.escrow_token_account.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        decimals: ctx.accounts.mint.decimals,
+        token_program: &ctx.accounts.token_program.to_account_info(),
+    };
+
+    let earnings_payer = EarningsPayer {
+        payer: &ctx.accounts.authority.to_account_info(),
+        system_program: &ctx.accounts.system_program.to_account_info(),
+    };
+
+    let settlement = settle_escrow(
+        &source,
+        payout,
+        &ctx.accounts.protocol_config,
+        ctx.accounts.fee_exemption.is_some(),
+        &earnings_payer,
+        ctx.remaining_accounts,
+        amount,
+    )?;
+
+    let entry = ctx.accounts.mint_registry
+        .find_mut(&ctx.accounts.mint.key())
+        .ok_or(ErrorCode::MintNotAccepted)?;
+    entry.record_settlement(&settlement)?;
+
+    Ok(())
+}
+
+// Distributes everything available in one track's escrow for `mint` as part of a
+// distribution run. Tracks whose registry shows they do not accept the mint, or that have
+// nothing available, are done without a payout. Returns false when the registry was not
+// passed, so the run stops there instead of dropping the track.
+fn distribute_run_track<'info>(
+    track: &Account<'info, Track>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &AccountInfo<'info>,
+    config: &ProtocolConfig,
+    earnings_payer: &EarningsPayer<'_, 'info>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<bool> {
+
+    let (registry_address, _) = Pubkey::find_program_address(
+        &[b"mint_registry".as_ref(), track.key().as_ref()],
+        &crate::ID,
+    );
+    let Some(registry_info) = remaining_accounts.iter().find(|acc| acc.key() == registry_address) else {
+        return Ok(false);
+    };
+
+    // An uninitialized registry PDA proves the track accepts no mints at all
+    if registry_info.data_is_empty() {
+        return Ok(true);
+    }
+    require_keys_eq!(*registry_info.owner, crate::ID, ErrorCode::InvalidArgs);
+
+    let mut registry = MintRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+    let Some(entry) = registry.find_mut(&mint.key()) else {
+        return Ok(true);
+    };
+
+    let escrow_info = remaining_accounts
+        .iter()
+        .find(|acc| acc.key() == entry.escrow)
+        .ok_or(ErrorCode::MissingEscrowAccount)?;
+    let escrow = InterfaceAccount::<TokenAccount>::try_from(escrow_info)?;
+    require!(escrow.owner == track.key(), ErrorCode::InvalidTokenAccountOwner);
+
+    let available = entry.available(escrow.amount);
+    if available == 0 {
+        return Ok(true);
+    }
+
+    let (exemption_address, _) = Pubkey::find_program_address(
+        &[b"fee_exemption".as_ref(), track.key().as_ref()],
+        &crate::ID,
+    );
+    let fee_exempt = remaining_accounts
+        .iter()
+        .any(|acc| acc.key() == exemption_address && *acc.owner == crate::ID);
+
+    let source = EscrowSource {
+        track,
+        escrow: escrow_info,
+        mint: &mint.to_account_info(),
+        decimals: mint.decimals,
+        token_program,
+    };
+
+    let settlement = settle_escrow(
+        &source,
+        Payout::Transfer,
+        config,
+        fee_exempt,
+        earnings_payer,
+        remaining_accounts,
+        available,
+    )?;
+
+    entry.record_settlement(&settlement)?;
+    registry.try_serialize(&mut &mut registry_info.try_borrow_mut_data()?[..])?;
+
+    Ok(true)
+}
+
+// Skims the protocol fee off `amount` into the fee recipient's ATA (looked up in
+// `remaining_accounts`) and returns the fee taken. Mints without a configured
+// recipient and exempt tracks are not charged.
+fn collect_protocol_fee<'info>(
+    source: &EscrowSource<'_, 'info>,
+    config: &ProtocolConfig,
+    exempt: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<u64> {
+
+    let recipient = match config.recipient_for(source.mint.key) {
+        Some(recipient) if !exempt => recipient,
+        _ => return Ok(0),
+    };
+
+    let fee = bps_of(amount, config.fee_bps)?;
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    let fee_token_account = source.ata_for(&recipient);
+    let to_account = remaining_accounts
+        .iter()
+        .find(|acc| acc.key() == fee_token_account)
+        .ok_or(ErrorCode::MissingFeeAccount)?;
+
+    source.transfer(to_account, fee)?;
+
+    emit!(ProtocolFeeCollected {
+        track_id: source.track.track_id,
+        mint: source.mint.key(),
+        amount: fee,
+        recipient,
+    });
+
+    Ok(fee)
+}
+
+// Label cut owed on an amount from a catalog track, paid to the catalog owner
+struct CatalogCut {
+    catalog: Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+}
+
+// Closes an account this program owns, sending its lamports to `destination`
+fn close_program_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination.lamports().checked_add(lamports).ok_or(ErrorCode::MathError)?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+// Checks that every member of a release is a track account of this program. Each member
+// track must be passed in `remaining_accounts`.
+fn verify_release_tracks(tracks: &[ReleaseTrack], remaining_accounts: &[AccountInfo]) -> Result<()> {
+
+    for member in tracks {
+        let track_info = remaining_accounts
+            .iter()
+            .find(|acc| acc.key() == member.track)
+            .ok_or(ErrorCode::InvalidReleaseTrack)?;
+        require_keys_eq!(*track_info.owner, crate::ID, ErrorCode::InvalidReleaseTrack);
+        Track::try_deserialize(&mut &track_info.try_borrow_data()?[..])
+            .map_err(|_| error!(ErrorCode::InvalidReleaseTrack))?;
+    }
+
+    Ok(())
+}
+
+// Works out the label cut for `track`, if it belongs to a catalog. The catalog account
+// must be passed in `remaining_accounts` so the cut cannot be skipped. The cut never
+// exceeds the split the track agreed to when it joined.
+fn catalog_cut(track: &Track, remaining_accounts: &[AccountInfo], amount: u64) -> Result<Option<CatalogCut>> {
+
+    let Some(catalog_key) = track.catalog else {
+        return Ok(None);
+    };
+
+    let catalog_info = remaining_accounts
+        .iter()
+        .find(|acc| acc.key() == catalog_key)
+        .ok_or(ErrorCode::MissingCatalogAccount)?;
+    require_keys_eq!(*catalog_info.owner, crate::ID, ErrorCode::MissingCatalogAccount);
+
+    let catalog = Catalog::try_deserialize(&mut &catalog_info.try_borrow_data()?[..])?;
+    let cut = bps_of(amount, catalog.label_share_bps.min(track.catalog_share_cap))?;
+    if cut == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(CatalogCut {
+        catalog: catalog_key,
+        recipient: catalog.owner,
+        amount: cut,
+    }))
+}
+
+// Accounts used to create contributor earnings records on their first payout
+struct EarningsPayer<'a, 'info> {
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+// What a single distribution did with the amount it was given
+pub struct Settlement {
+    pub protocol_fee: u64,
+    pub label_cut: u64,
+    pub paid: u64,
+    // Shares left in the escrow as contributor claimable balances
+    pub credited: u64,
+    pub dust: u64,
+}
+
+impl Settlement {
+    // Amount that left the escrow for rightsholders, label cut included
+    pub fn distributed(&self) -> Result<u64> {
+        Ok(self.paid.checked_add(self.label_cut).ok_or(ErrorCode::MathError)?)
+    }
+
+    // Everything the settlement was given to split
+    pub fn amount(&self) -> Result<u64> {
+        [self.label_cut, self.paid, self.credited, self.dust]
+            .iter()
+            .try_fold(self.protocol_fee, |total, part| total.checked_add(*part))
+            .ok_or(error!(ErrorCode::MathError))
+    }
+
+    // Dust left over after this settlement. Earlier remainders sit in the escrow ahead of
+    // anything deposited since, so a settlement sweeps them first.
+    pub fn carry_dust(&self, outstanding: u64) -> Result<u64> {
+        Ok(outstanding
+            .saturating_sub(self.amount()?)
+            .checked_add(self.dust)
+            .ok_or(ErrorCode::MathError)?)
+    }
+}
+
+// Whether a distribution pays contributors out now or credits their claimable balances
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Payout {
+    Transfer,
+    Credit,
+}
+
+// How a contributor's share was settled: paid straight to their wallet, or credited
+// to their claimable balance in the escrow
+pub enum EarningsCredit {
+    Paid(u64),
+    Claimable(u64),
+}
+
+// Records a contributor's share for `mint` on their earnings PDA, which is looked up in
+// `remaining_accounts` and created on first payout. Paid amounts also go to the
+// contributor's profile aggregate if the profile is passed.
+fn credit_earnings<'info>(
+    track: &Pubkey,
+    contributor: &Pubkey,
+    mint: &Pubkey,
+    credit: EarningsCredit,
+    earnings_payer: &EarningsPayer<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+
+    let (address, bump) = Pubkey::find_program_address(
+        &[b"earnings".as_ref(), track.as_ref(), contributor.as_ref()],
+        &crate::ID,
+    );
+
+    let info = remaining_accounts
+        .iter()
+        .find(|acc| acc.key() == address)
+        .ok_or(ErrorCode::MissingEarningsAccount)?;
+
+    let mut earnings = if info.data_is_empty() {
+        let space = 8 + ContributorEarnings::INIT_SPACE;
+        let seeds = &[b"earnings".as_ref(), track.as_ref(), contributor.as_ref(), &[bump]];
+        create_pda_account(earnings_payer, info, space, &[&seeds[..]])?;
+
+        ContributorEarnings {
+            track: *track,
+            contributor: *contributor,
+            totals: Vec::new(),
+            bump,
+        }
+    } else {
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::MissingEarningsAccount);
+        ContributorEarnings::try_deserialize(&mut &info.try_borrow_data()?[..])?
+    };
+
+    let entry = earnings.entry_mut(mint)?;
+    match credit {
+        EarningsCredit::Paid(amount) => {
+            entry.paid = entry.paid.checked_add(amount).ok_or(ErrorCode::MathError)?;
+            earnings.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            credit_profile(contributor, mint, amount, remaining_accounts)?;
+        }
+        EarningsCredit::Claimable(amount) => {
+            entry.claimable = entry.claimable.checked_add(amount).ok_or(ErrorCode::MathError)?;
+            earnings.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+    }
+
+    Ok(())
+}
+
+// Cross-track aggregate on the contributor's opt-in profile, when it is passed. This is
+// best-effort: callers that leave the profile out skip it, and ContributorEarnings stays
+// the authoritative per-track record.
+fn credit_profile(contributor: &Pubkey, mint: &Pubkey, amount: u64, remaining_accounts: &[AccountInfo]) -> Result<()> {
+
+    let (profile_address, _) = Pubkey::find_program_address(
+        &[b"profile".as_ref(), contributor.as_ref()],
+        &crate::ID,
+    );
+
+    if let Some(profile_info) = remaining_accounts.iter().find(|acc| acc.key() == profile_address) {
+        require_keys_eq!(*profile_info.owner, crate::ID, ErrorCode::InvalidArgs);
+        let mut profile = ContributorProfile::try_deserialize(&mut &profile_info.try_borrow_data()?[..])?;
+        add_mint_total(&mut profile.earnings, mint, amount)?;
+        profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ProfileEarningsCredited {
+            wallet: *contributor,
+            mint: *mint,
+            amount,
+        });
+    }
+
+    Ok(())
+}
+
+fn add_mint_total(totals: &mut Vec<MintTotal>, mint: &Pubkey, amount: u64) -> Result<()> {
+    match totals.iter_mut().find(|total| total.mint == *mint) {
+        Some(total) => total.amount = total.amount.checked_add(amount).ok_or(ErrorCode::MathError)?,
+        None => {
+            require!(totals.len() < MAX_ACCEPTED_MINTS + 1, ErrorCode::TooManyAcceptedMints);
+            totals.push(MintTotal { mint: *mint, amount });
+        }
+    }
+    Ok(())
+}
+
+// Lists `track` on the profile of every contributor whose profile PDA is in `remaining_accounts`.
+// Best-effort like credit_profile; unlisting on removal is not.
+fn link_profiles(track: &Pubkey, contributors: &[Pubkey], remaining_accounts: &[AccountInfo]) -> Result<()> {
+
+    for contributor in contributors.iter() {
+        let (profile_address, _) = Pubkey::find_program_address(
+            &[b"profile".as_ref(), contributor.as_ref()],
+            &crate::ID,
+        );
+
+        let Some(profile_info) = remaining_accounts.iter().find(|acc| acc.key() == profile_address) else {
+            continue;
+        };
+
+        require_keys_eq!(*profile_info.owner, crate::ID, ErrorCode::InvalidArgs);
+        let mut profile = ContributorProfile::try_deserialize(&mut &profile_info.try_borrow_data()?[..])?;
+
+        if profile.tracks.contains(track) {
+            continue;
+        }
+
+        require!(profile.tracks.len() < MAX_PROFILE_TRACKS, ErrorCode::TooManyProfileTracks);
+        profile.tracks.push(*track);
+        profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ProfileTrackLinked {
+            wallet: *contributor,
+            track: *track,
+        });
+    }
+
+    Ok(())
+}
+
+// Drops `track` from the profiles of contributors removed from its split. The profile PDA of
+// every removed contributor must be passed, even if it was never created, so a profile can't
+// keep listing a track it left.
+fn unlink_profiles(track: &Pubkey, removed: &[Pubkey], remaining_accounts: &[AccountInfo]) -> Result<()> {
+
+    for contributor in removed.iter() {
+        let (profile_address, _) = Pubkey::find_program_address(
+            &[b"profile".as_ref(), contributor.as_ref()],
+            &crate::ID,
+        );
+
+        let profile_info = remaining_accounts
+            .iter()
+            .find(|acc| acc.key() == profile_address)
+            .ok_or(ErrorCode::MissingProfileAccount)?;
+
+        if *profile_info.owner != crate::ID {
+            continue;
+        }
+
+        let mut profile = ContributorProfile::try_deserialize(&mut &profile_info.try_borrow_data()?[..])?;
+        profile.tracks.retain(|t| t != track);
+        profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ProfileTrackUnlinked {
+            wallet: *contributor,
+            track: *track,
+        });
+    }
+
+    Ok(())
+}
+
+// Allocates a program owned PDA from inside an instruction, the same way `init` does,
+// including when someone has already sent lamports to the address
+fn create_pda_account<'info>(
+    earnings_payer: &EarningsPayer<'_, 'info>,
+    account: &AccountInfo<'info>,
+    space: usize,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+
+    let system_program = earnings_payer.system_program.clone();
+    let required = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        let cpi_accounts = anchor_lang::system_program::CreateAccount {
+            from: earnings_payer.payer.clone(),
+            to: account.clone(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(system_program, cpi_accounts, signer);
+        return anchor_lang::system_program::create_account(cpi_ctx, required, space as u64, &crate::ID);
+    }
+
+    let top_up = required.saturating_sub(account.lamports());
+    if top_up > 0 {
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: earnings_payer.payer.clone(),
+            to: account.clone(),
+        };
+        anchor_lang::system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), top_up)?;
+    }
+
+    let cpi_accounts = anchor_lang::system_program::Allocate {
+        account_to_allocate: account.clone(),
+    };
+    anchor_lang::system_program::allocate(CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer), space as u64)?;
+
+    let cpi_accounts = anchor_lang::system_program::Assign {
+        account_to_assign: account.clone(),
+    };
+    anchor_lang::system_program::assign(CpiContext::new_with_signer(system_program, cpi_accounts, signer), &crate::ID)
+}
+
+// Current owner of the stem NFT behind a royalty position. The stem account and the
+// holder's token account for its mint are looked up in `remaining_accounts`.
+fn stem_holder(stem_key: &Pubkey, remaining_accounts: &[AccountInfo]) -> Result<Pubkey> {
+
+    let stem_info = remaining_accounts
+        .iter()
+        .find(|acc| acc.key() == *stem_key)
+        .ok_or(ErrorCode::MissingStemHolderAccount)?;
+    require_keys_eq!(*stem_info.owner, crate::ID, ErrorCode::MissingStemHolderAccount);
+    let stem = Stem::try_deserialize(&mut &stem_info.try_borrow_data()?[..])?;
+    let stem_mint = stem.mint.ok_or(ErrorCode::MissingStemHolderAccount)?;
+
+    for acc in remaining_accounts.iter() {
+        if *acc.owner != anchor_spl::token::ID && *acc.owner != anchor_spl::token_2022::ID {
+            continue;
+        }
+        let Ok(token_account) = TokenAccount::try_deserialize(&mut &acc.try_borrow_data()?[..]) else {
+            continue;
+        };
+        if token_account.mint == stem_mint && token_account.amount == 1 {
+            return Ok(token_account.owner);
+        }
+    }
+
+    err!(ErrorCode::MissingStemHolderAccount)
+}
+
+// Pays every contributor their bps share of `amount` out of a track-owned escrow and
+// returns the total paid. Contributor ATAs and earnings records are looked up in `remaining_accounts`.
+fn distribute_to_contributors<'info>(
+    source: &EscrowSource<'_, 'info>,
+    payout: Payout,
+    earnings_payer: &EarningsPayer<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<u64> {
+
+    let track = source.track;
+    let total_bps: u64 = track.payees().iter().map(|(_, bps)| *bps as u64).sum();
+    require!(total_bps == 10000, ErrorCode::InvalidShareTotal);
+
+    let mut paid: u64 = 0;
+
+    for (contributor, share_bps) in track.payees() {
+        let share_amount = bps_of(amount, share_bps)?;
+
+        if share_amount == 0 {
+            continue;
+        }
+
+        if payout == Payout::Credit {
+            credit_earnings(&track.key(), &contributor, source.mint.key, EarningsCredit::Claimable(share_amount), earnings_payer, remaining_accounts)?;
+            paid = paid.checked_add(share_amount).ok_or(ErrorCode::MathError)?;
+            continue;
+        }
+
+        // Stem royalty positions are paid to whoever holds the stem NFT right now
+        let recipient = if track.is_royalty_stem(&contributor) {
+            stem_holder(&contributor, remaining_accounts)?
+        } else {
+            contributor
+        };
+
+        let contributor_token_account = source.ata_for(&recipient);
+
+        let to_account = remaining_accounts
+            .iter()
+            .find(|acc| acc.key() == contributor_token_account)
+            .ok_or(ErrorCode::InvalidArgs)?;
+
+        source.transfer(to_account, share_amount)?;
+
+        if recipient != contributor {
+            emit!(StemRoyaltyPaid {
+                track: track.key(),
+                stem: contributor,
+                holder: recipient,
+                mint: source.mint.key(),
+                amount: share_amount,
+            });
+        }
+
+        credit_earnings(&track.key(), &contributor, source.mint.key, EarningsCredit::Paid(share_amount), earnings_payer, remaining_accounts)?;
+        paid = paid.checked_add(share_amount).ok_or(ErrorCode::MathError)?;
+    }
+
+    Ok(paid)
+}
+
+// Runs the fee skim and contributor split for one escrow and emits the result
+fn settle_escrow<'info>(
+    source: &EscrowSource<'_, 'info>,
+    payout: Payout,
+    config: &ProtocolConfig,
+    fee_exempt: bool,
+    earnings_payer: &EarningsPayer<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<Settlement> {
+
+    let protocol_fee = collect_protocol_fee(source, config, fee_exempt, remaining_accounts, amount)?;
+    let mut net_amount = amount.checked_sub(protocol_fee).ok_or(ErrorCode::MathError)?;
+
+    // The label takes its cut before the track split, always paid out directly
+    let mut label_cut = 0;
+    if let Some(cut) = catalog_cut(source.track, remaining_accounts, net_amount)? {
+        let recipient_token_account = source.ata_for(&cut.recipient);
+        let to_account = remaining_accounts
+            .iter()
+            .find(|acc| acc.key() == recipient_token_account)
+            .ok_or(ErrorCode::MissingCatalogAccount)?;
+
+        source.transfer(to_account, cut.amount)?;
+
+        emit!(LabelCutCollected {
+            track_id: source.track.track_id,
+            catalog: cut.catalog,
+            mint: source.mint.key(),
+            amount: cut.amount,
+            recipient: cut.recipient,
+        });
+
+        label_cut = cut.amount;
+        net_amount -= cut.amount;
+    }
+
+    let split = distribute_to_contributors(source, payout, earnings_payer, remaining_accounts, net_amount)?;
+    let (paid, credited) = match payout {
+        Payout::Transfer => (split, 0),
+        Payout::Credit => (0, split),
+    };
+
+    emit!(EscrowDistributed {
+        track_id: source.track.track_id,
+        mint: source.mint.key(),
+        amount,
+        protocol_fee,
+    });
+
+    Ok(Settlement {
+        protocol_fee,
+        label_cut,
+        paid,
+        credited,
+        dust: net_amount - split,
+    })
+}
+
+// "<track title> - <stem type>", cut to the Metaplex name limit
+fn stem_nft_name(title: &str, stem_type: StemType) -> String {
+    metadata_name(format!("{} - {}", title, stem_type.label()))
+}
+
+fn metadata_name(mut name: String) -> String {
+    if name.len() > MAX_NAME_LENGTH {
+        let mut end = MAX_NAME_LENGTH;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+    name
+}
+
+// Track contributors as Metaplex creators. A contributor listed more than once appears once
+// with their shares summed, and only the five largest are kept. Their shares are rescaled to
+// whole percentages of what is listed, rounded down, with the remainder going to the largest.
+// Stem royalty positions are left out: they are PDAs that cannot receive marketplace royalties.
+fn stem_nft_creators(track: &Track) -> Result<Vec<Creator>> {
+    let mut splits: Vec<(Pubkey, u16)> = Vec::with_capacity(track.contributors.len());
+    for (contributor, bps) in track.contributors.iter().zip(track.shares.iter()) {
+        // Metaplex rejects duplicate creator addresses
+        match splits.iter_mut().find(|(address, _)| address == contributor) {
+            Some((_, total)) => *total = total.checked_add(*bps).ok_or(ErrorCode::MathError)?,
+            None => splits.push((*contributor, *bps)),
+        }
+    }
+
+    // Metadata holds at most five creators
+    splits.sort_by_key(|(_, bps)| std::cmp::Reverse(*bps));
+    splits.truncate(MAX_CREATOR_LIMIT);
+
+    let listed: u64 = splits.iter().map(|(_, bps)| *bps as u64).sum();
+    let mut creators = Vec::with_capacity(splits.len());
+    for (address, bps) in splits {
+        // Every listed share is zero only if royalty stems took the whole split
+        let share = (bps as u64)
+            .checked_mul(100)
+            .ok_or(ErrorCode::MathError)?
+            .checked_div(listed)
+            .unwrap_or(0);
+        creators.push(Creator {
+            address,
+            verified: false,
+            share: share as u8,
+        });
+    }
+
+    let assigned: u8 = creators.iter().map(|c| c.share).sum();
+    if let Some(first) = creators.first_mut() {
+        first.share += 100 - assigned;
+    }
+
+    Ok(creators)
+}
+
+    #[event]
+    pub struct EarningsClaimed {
+        pub track_id: u64,
+        pub track: Pubkey,
+        pub contributor: Pubkey,
+        pub mint: Pubkey,
+        pub amount: u64,
+    }
+
+    #[derive(Accounts)]
+    pub struct ClaimEarnings<'info> {
+        pub contributor: Signer<'info>,
+
+        #[account(
+            mut,
+            token::mint = mint,
+            token::authority = contributor,
+            token::token_program = token_program,
+        )]
+        pub contributor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            mut,
+            seeds = [b"profile".as_ref(), contributor.key().as_ref()],
+            bump = profile.bump,
+        )]
+        pub profile: Option<Account<'info, ContributorProfile>>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    #[event]
+    pub struct AcceptedMintAdded {
+        pub track_id: u64,
+        pub mint: Pubkey,
+        pub escrow: Pubkey,
+    }
+
+    #[event]
+    pub struct AcceptedMintRemoved {
+        pub track_id: u64,
+        pub mint: Pubkey,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct AddAcceptedMint<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            init_if_needed,
+            payer = authority,
+            space = 8 + MintRegistry::INIT_SPACE,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            init_if_needed,
+            payer = authority,
+            associated_token::mint = mint,
+            associated_token::authority = track,
+            associated_token::token_program = token_program,
+        )]
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct RemoveAcceptedMint<'info> {
+        pub authority: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct EscrowDistributeAll<'info> {
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            seeds = [b"fee_exemption".as_ref(), track.key().as_ref()],
+            bump = fee_exemption.bump,
+        )]
+        pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+        // Pays rent for contributor earnings records created on first payout
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        pub token_program: Program<'info, Token>,
+        pub token_2022_program: Program<'info, Token2022>,
+        pub system_program: Program<'info, System>,
+    }
+
+    // Resumable distribution over many tracks of one authority, settled in track order
+    #[account]
+    #[derive(InitSpace)]
+    pub struct DistributionRun {
+        pub authority: Pubkey,
+        pub mint: Pubkey,
+
+        #[max_len(MAX_RUN_TRACKS)]
+        pub tracks: Vec<Pubkey>,
+        // Index of the next track to settle
+        pub cursor: u32,
+        pub bump: u8,
+    }
+
+    impl DistributionRun {
+        pub fn is_complete(&self) -> bool {
+            self.cursor as usize >= self.tracks.len()
+        }
+    }
+
+    #[event]
+    pub struct DistributionRunStarted {
+        pub authority: Pubkey,
+        pub mint: Pubkey,
+        pub total: u32,
+    }
+
+    #[event]
+    pub struct DistributionRunProgress {
+        pub authority: Pubkey,
+        pub mint: Pubkey,
+        pub processed: u32,
+        pub cursor: u32,
+        pub total: u32,
+    }
+
+    #[derive(Accounts)]
+    pub struct StartDistributionRun<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            init_if_needed,
+            payer = authority,
+            space = 8 + DistributionRun::INIT_SPACE,
+            seeds = [b"distribution_run".as_ref(), authority.key().as_ref()],
+            bump,
+        )]
+        pub distribution_run: Account<'info, DistributionRun>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct ContinueDistributionRun<'info> {
+        // Pays rent for contributor earnings records created on first payout
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"distribution_run".as_ref(), authority.key().as_ref()],
+            bump = distribution_run.bump,
+            has_one = authority,
+        )]
+        pub distribution_run: Account<'info, DistributionRun>,
+
+        #[account(
+            address = distribution_run.mint,
+            mint::token_program = token_program,
+        )]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[event]
+    pub struct SolDeposited {
+        pub track_id: u64,
+        pub depositor: Pubkey,
+        pub amount: u64,
+    }
+
+    #[event]
+    pub struct SolDistributed {
+        pub track_id: u64,
+        pub amount: u64,
+        pub protocol_fee: u64,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, authority: Pubkey)]
+    pub struct CreateSolVault<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            init,
+            payer = payer,
+            space = 8 + SolVault::INIT_SPACE,
+            seeds = [b"sol_vault".as_ref(), track.key().as_ref()],
+            bump,
+        )]
+        pub sol_vault: Account<'info, SolVault>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(amount: u64, track_id: u64, authority: Pubkey)]
+    pub struct SolDeposit<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            seeds = [b"sol_vault".as_ref(), track.key().as_ref()],
+            bump = sol_vault.bump,
+        )]
+        pub sol_vault: Account<'info, SolVault>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(amount: u64, track_id: u64)]
+    pub struct SolDistribute<'info> {
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            seeds = [b"sol_vault".as_ref(), track.key().as_ref()],
+            bump = sol_vault.bump,
+        )]
+        pub sol_vault: Account<'info, SolVault>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            seeds = [b"fee_exemption".as_ref(), track.key().as_ref()],
+            bump = fee_exemption.bump,
+        )]
+        pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+        // Pays rent for contributor earnings records created on first payout
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct ClaimSolEarnings<'info> {
+        #[account(mut)]
+        pub contributor: Signer<'info>,
+
+        #[account(constraint = track.track_id == track_id @ ErrorCode::InvalidArgs)]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            seeds = [b"sol_vault".as_ref(), track.key().as_ref()],
+            bump = sol_vault.bump,
+        )]
+        pub sol_vault: Account<'info, SolVault>,
+
+        #[account(
+            mut,
+            seeds = [b"earnings".as_ref(), track.key().as_ref(), contributor.key().as_ref()],
+            bump = earnings.bump,
+        )]
+        pub earnings: Account<'info, ContributorEarnings>,
+
+        #[account(
+            mut,
+            seeds = [b"profile".as_ref(), contributor.key().as_ref()],
+            bump = profile.bump,
+        )]
+        pub profile: Option<Account<'info, ContributorProfile>>,
+    }
+
+    // Program owned lamport vault for native SOL royalties
+    #[account]
+    #[derive(InitSpace)]
+    pub struct SolVault {
+        pub track: Pubkey,
+        pub bump: u8,
+
+        pub total_deposited: u64,
+        pub total_distributed: u64,
+        pub total_protocol_fees: u64,
+        pub dust_outstanding: u64,
+        // Shares held back for wallets too empty to receive them, owed via claim_sol_earnings
+        // (or claim_stem_royalty_sol when the share belongs to a stem royalty position)
+        pub credited_outstanding: u64,
+    }
+
+    impl SolVault {
+        pub fn record_settlement(&mut self, settlement: &Settlement) -> Result<()> {
+            self.total_distributed = self.total_distributed.checked_add(settlement.distributed()?).ok_or(ErrorCode::MathError)?;
+            self.total_protocol_fees = self.total_protocol_fees.checked_add(settlement.protocol_fee).ok_or(ErrorCode::MathError)?;
+            self.dust_outstanding = settlement.carry_dust(self.dust_outstanding)?;
+            self.credited_outstanding = self.credited_outstanding.checked_add(settlement.credited).ok_or(ErrorCode::MathError)?;
+            Ok(())
+        }
+    }
+
+    #[event]
+    pub struct ProfileUpdated {
+        pub wallet: Pubkey,
+        pub display_name: String,
+        pub profile_cid: String,
+    }
+
+    // Profile totals are best-effort: these are only emitted when the instruction was passed the
+    // profile, so a payout or track without one left the profile untouched
+    #[event]
+    pub struct ProfileEarningsCredited {
+        pub wallet: Pubkey,
+        pub mint: Pubkey,
+        pub amount: u64,
+    }
+
+    #[event]
+    pub struct ProfileTrackLinked {
+        pub wallet: Pubkey,
+        pub track: Pubkey,
+    }
+
+    #[event]
+    pub struct ProfileTrackUnlinked {
+        pub wallet: Pubkey,
+        pub track: Pubkey,
+    }
+
+    #[derive(Accounts)]
+    pub struct CreateProfile<'info> {
+        #[account(mut)]
+        pub wallet: Signer<'info>,
+
+        #[account(
+            init,
+            payer = wallet,
+            space = 8 + ContributorProfile::INIT_SPACE,
+            seeds = [b"profile".as_ref(), wallet.key().as_ref()],
+            bump,
+        )]
+        pub profile: Account<'info, ContributorProfile>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct UpdateProfile<'info> {
+        pub wallet: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"profile".as_ref(), wallet.key().as_ref()],
+            bump = profile.bump,
+            has_one = wallet,
+        )]
+        pub profile: Account<'info, ContributorProfile>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    // One per wallet, listing the tracks it contributes to and its earnings across all of them.
+    // Both are best-effort: `tracks` only gains a track when initialize_track or update_shares
+    // was passed this profile, and `earnings` only counts payouts whose transaction passed it.
+    // Removal from a track always requires the profile. Per-track ContributorEarnings records
+    // and the track's contributor list are authoritative.
+    #[account]
+    #[derive(InitSpace)]
+    pub struct ContributorProfile {
+        pub wallet: Pubkey,
+
+        #[max_len(MAX_DISPLAY_NAME_LEN)]
+        pub display_name: String,
+
+        #[max_len(MAX_CID_LEN)]
+        pub profile_cid: String,
+
+        #[max_len(MAX_PROFILE_TRACKS)]
+        pub tracks: Vec<Pubkey>,
+
+        #[max_len(MAX_ACCEPTED_MINTS + 1)]
+        pub earnings: Vec<MintTotal>,
+        pub bump: u8,
+    }
+
+    impl ContributorProfile {
+        pub fn set_details(&mut self, display_name: Option<String>, profile_cid: Option<String>) -> Result<()> {
+            if let Some(display_name) = display_name {
+                require!(display_name.len() <= MAX_DISPLAY_NAME_LEN, ErrorCode::DisplayNameTooLong);
+                self.display_name = display_name;
+            }
+            if let Some(profile_cid) = profile_cid {
+                require!(profile_cid.len() <= MAX_CID_LEN, ErrorCode::CidTooLong);
+                self.profile_cid = profile_cid;
+            }
+            Ok(())
+        }
+    }
+
+    // A label or artist grouping tracks. Member tracks store the catalog key, so changing
+    // the owner moves all of them together.
+    #[account]
+    #[derive(InitSpace)]
+    pub struct Catalog {
+        pub creator: Pubkey,
+        pub catalog_id: u64,
+        pub owner: Pubkey,
+
+        #[max_len(MAX_CATALOG_ADMINS)]
+        pub admins: Vec<Pubkey>,
+
+        #[max_len(MAX_TITLE_LEN)]
+        pub name: String,
+        // Taken from member track payouts before the track split
+        pub label_share_bps: u16,
+        pub track_count: u32,
+        pub bump: u8,
+    }
+
+    impl Catalog {
+        pub fn is_admin(&self, key: &Pubkey) -> bool {
+            self.owner == *key || self.admins.contains(key)
+        }
+    }
+
+    #[event]
+    pub struct CatalogCreated {
+        pub catalog: Pubkey,
+        pub owner: Pubkey,
+        pub label_share_bps: u16,
+    }
+
+    #[event]
+    pub struct CatalogAdminsUpdated {
+        pub catalog: Pubkey,
+        pub admins: Vec<Pubkey>,
+    }
+
+    #[event]
+    pub struct CatalogSplitUpdated {
+        pub catalog: Pubkey,
+        pub label_share_bps: u16,
+    }
+
+    #[event]
+    pub struct CatalogTransferred {
+        pub catalog: Pubkey,
+        pub previous_owner: Pubkey,
+        pub new_owner: Pubkey,
+        pub track_count: u32,
+    }
+
+    #[event]
+    pub struct CatalogTrackAdded {
+        pub catalog: Pubkey,
+        pub track: Pubkey,
+    }
+
+    #[event]
+    pub struct CatalogTrackRemoved {
+        pub catalog: Pubkey,
+        pub track: Pubkey,
+    }
+
+    #[event]
+    pub struct LabelCutCollected {
+        pub track_id: u64,
+        pub catalog: Pubkey,
+        pub mint: Pubkey,
+        pub amount: u64,
+        pub recipient: Pubkey,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(catalog_id: u64)]
+    pub struct CreateCatalog<'info> {
+        #[account(mut)]
+        pub owner: Signer<'info>,
+
+        #[account(
+            init,
+            payer = owner,
+            space = 8 + Catalog::INIT_SPACE,
+            seeds = [b"catalog".as_ref(), owner.key().as_ref(), catalog_id.to_le_bytes().as_ref()],
+            bump,
+        )]
+        pub catalog: Account<'info, Catalog>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct ManageCatalog<'info> {
+        pub owner: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"catalog".as_ref(), catalog.creator.as_ref(), catalog.catalog_id.to_le_bytes().as_ref()],
+            bump = catalog.bump,
+            has_one = owner,
+        )]
+        pub catalog: Account<'info, Catalog>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct AddTrackToCatalog<'info> {
+        pub authority: Signer<'info>,
+
+        pub admin: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"catalog".as_ref(), catalog.creator.as_ref(), catalog.catalog_id.to_le_bytes().as_ref()],
+            bump = catalog.bump,
+        )]
+        pub catalog: Account<'info, Catalog>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct RemoveTrackFromCatalog<'info> {
+        pub admin: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"catalog".as_ref(), catalog.creator.as_ref(), catalog.catalog_id.to_le_bytes().as_ref()],
+            bump = catalog.bump,
+        )]
+        pub catalog: Account<'info, Catalog>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(),
+                track.authority.as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            constraint = track.catalog == Some(catalog.key()) @ ErrorCode::TrackNotInCatalog,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct LeaveCatalog<'info> {
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"catalog".as_ref(), catalog.creator.as_ref(), catalog.catalog_id.to_le_bytes().as_ref()],
+            bump = catalog.bump,
+        )]
+        pub catalog: Account<'info, Catalog>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = track.catalog == Some(catalog.key()) @ ErrorCode::TrackNotInCatalog,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+    pub struct ReleaseTrack {
+        pub track: Pubkey,
+        // Share of release-level payments routed to this track
+        pub weight_bps: u16,
+    }
+
+    // An album or EP: an ordered list of tracks sold together
+    #[account]
+    #[derive(InitSpace)]
+    pub struct Release {
+        pub authority: Pubkey,
+        pub release_id: u64,
+
+        #[max_len(MAX_TITLE_LEN)]
+        pub title: String,
+
+        #[max_len(MAX_UPC_LEN)]
+        pub upc: String,
+        pub release_date: i64,
+
+        #[max_len(MAX_CID_LEN)]
+        pub cover_cid: String,
+
+        #[max_len(MAX_RELEASE_TRACKS)]
+        pub tracks: Vec<ReleaseTrack>,
+        pub bump: u8,
+    }
+
+    impl Release {
+        pub fn set_details(&mut self, title: Option<String>, upc: Option<String>, cover_cid: Option<String>) -> Result<()> {
+            if let Some(title) = title {
+                require!(title.len() <= MAX_TITLE_LEN, ErrorCode::TitleTooLong);
+                self.title = title;
+            }
+            if let Some(upc) = upc {
+                // UPC-A or EAN-13
+                require!(upc.len() == 12 || upc.len() == 13, ErrorCode::InvalidUpc);
+                require!(upc.bytes().all(|b| b.is_ascii_digit()), ErrorCode::InvalidUpc);
+                self.upc = upc;
+            }
+            if let Some(cover_cid) = cover_cid {
+                require!(cover_cid.len() <= MAX_CID_LEN, ErrorCode::CidTooLong);
+                self.cover_cid = cover_cid;
+            }
+            Ok(())
+        }
+
+        pub fn set_tracks(&mut self, tracks: Vec<ReleaseTrack>) -> Result<()> {
+            require!(!tracks.is_empty(), ErrorCode::InvalidArgs);
+            require!(tracks.len() <= MAX_RELEASE_TRACKS, ErrorCode::TooManyReleaseTracks);
+
+            let sum: u64 = tracks.iter().map(|t| t.weight_bps as u64).sum();
+            require!(sum == 10000, ErrorCode::InvalidShareTotal);
+
+            for (i, member) in tracks.iter().enumerate() {
+                require!(
+                    !tracks[..i].iter().any(|other| other.track == member.track),
+                    ErrorCode::DuplicateReleaseTrack
+                );
+            }
+
+            self.tracks = tracks;
+            Ok(())
+        }
+    }
+
+    #[event]
+    pub struct ReleaseUpdated {
+        pub release: Pubkey,
+        pub upc: String,
+        pub release_date: i64,
+        pub tracks: Vec<ReleaseTrack>,
+    }
+
+    #[event]
+    pub struct ReleaseDeposited {
+        pub release: Pubkey,
+        pub depositor: Pubkey,
+        pub mint: Pubkey,
+        pub amount: u64,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(release_id: u64)]
+    pub struct CreateRelease<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            init,
+            payer = authority,
+            space = 8 + Release::INIT_SPACE,
+            seeds = [b"release".as_ref(), authority.key().as_ref(), release_id.to_le_bytes().as_ref()],
+            bump,
+        )]
+        pub release: Account<'info, Release>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(release_id: u64)]
+    pub struct UpdateRelease<'info> {
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"release".as_ref(), authority.key().as_ref(), release_id.to_le_bytes().as_ref()],
+            bump = release.bump,
+            has_one = authority,
+        )]
+        pub release: Account<'info, Release>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(Accounts)]
+    pub struct DepositToRelease<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        #[account(
+            seeds = [b"release".as_ref(), release.authority.as_ref(), release.release_id.to_le_bytes().as_ref()],
+            bump = release.bump,
+        )]
+        pub release: Account<'info, Release>,
+
+        #[account(
+            mut,
+            token::mint = mint,
+            token::token_program = token_program,
+        )]
+        pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+    pub struct MintTotal {
+        pub mint: Pubkey,
+        pub amount: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+    pub struct EarningsEntry {
+        pub mint: Pubkey,
+        pub paid: u64,
+        // Credited by escrow_credit and still sitting in the track escrow
+        pub claimable: u64,
+    }
+
+    // Lifetime amount paid to one contributor of one track, per mint, plus what they can
+    // still claim. Native SOL payouts are recorded under the native mint.
+    #[account]
+    #[derive(InitSpace)]
+    pub struct ContributorEarnings {
+        pub track: Pubkey,
+        pub contributor: Pubkey,
+
+        #[max_len(MAX_ACCEPTED_MINTS + 1)]
+        pub totals: Vec<EarningsEntry>,
+        pub bump: u8,
+    }
+
+    impl ContributorEarnings {
+        pub fn entry_mut(&mut self, mint: &Pubkey) -> Result<&mut EarningsEntry> {
+            if let Some(position) = self.totals.iter().position(|entry| entry.mint == *mint) {
+                return Ok(&mut self.totals[position]);
+            }
+
+            require!(self.totals.len() < MAX_ACCEPTED_MINTS + 1, ErrorCode::TooManyAcceptedMints);
+            self.totals.push(EarningsEntry { mint: *mint, paid: 0, claimable: 0 });
+            Ok(self.totals.last_mut().unwrap())
+        }
+    }
+
+    #[event]
+    pub struct ProtocolFeeUpdated {
+        pub fee_bps: u16,
+        pub max_fee_bps: u16,
+    }
+
+    #[event]
+    pub struct ProtocolFeeCollected {
+        pub track_id: u64,
+        pub mint: Pubkey,
+        pub amount: u64,
+        pub recipient: Pubkey,
+    }
+
+    #[event]
+    pub struct EscrowDistributed {
+        pub track_id: u64,
+        pub mint: Pubkey,
+        pub amount: u64,
+        pub protocol_fee: u64,
+    }
+
+    #[event]
+    pub struct FeeExemptionUpdated {
+        pub track: Pubkey,
+        pub exempt: bool,
+    }
+
+    #[derive(Accounts)]
+    pub struct InitializeProtocolConfig<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+
+        #[account(
+            init,
+            payer = admin,
+            space = 8 + ProtocolConfig::INIT_SPACE,
+            seeds = [b"protocol_config".as_ref()],
+            bump,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        // Only the program's upgrade authority may create the config
+        #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized)]
+        pub program: Program<'info, crate::program::Muzica>,
+
+        #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+        pub program_data: Account<'info, ProgramData>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct UpdateProtocolConfig<'info> {
+        pub admin: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            has_one = admin @ ErrorCode::Unauthorized,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(Accounts)]
+    pub struct AddFeeExemption<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            has_one = admin @ ErrorCode::Unauthorized,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub track: Account<'info, Track>,
+
+        #[account(
+            init,
+            payer = admin,
+            space = 8 + FeeExemption::INIT_SPACE,
+            seeds = [b"fee_exemption".as_ref(), track.key().as_ref()],
+            bump,
+        )]
+        pub fee_exemption: Account<'info, FeeExemption>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct RemoveFeeExemption<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            has_one = admin @ ErrorCode::Unauthorized,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            close = admin,
+            seeds = [b"fee_exemption".as_ref(), fee_exemption.track.as_ref()],
+            bump = fee_exemption.bump,
+        )]
+        pub fee_exemption: Account<'info, FeeExemption>,
+    }
+
+    #[event]
+    pub struct PauseUpdated {
+        pub track: Option<Pubkey>,
+        pub paused: bool,
+    }
+
+    #[derive(Accounts)]
+    pub struct SetGlobalPause<'info> {
+        pub guardian: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            has_one = guardian @ ErrorCode::Unauthorized,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct SetTrackPause<'info> {
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+        )]
+        pub track: Account<'info, Track>,
+    }
+
+    #[event]
+    pub struct TrackClosed {
+        pub track_id: u64,
+        pub authority: Pubkey,
+        pub escrows_closed: u8,
+    }
+
+    // Rounding dust paid out when a track is closed
+    #[event]
+    pub struct DustSwept {
+        pub track: Pubkey,
+        pub mint: Pubkey,
+        pub recipient: Pubkey,
+        pub amount: u64,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct CloseTrack<'info> {
+        // Receives the rent of every closed account
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            close = authority,
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        /// CHECK: the track's mint registry PDA; closed by the handler if it was ever created
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump,
+        )]
+        pub mint_registry: UncheckedAccount<'info>,
+
+        /// CHECK: the track's SOL vault PDA; closed by the handler if it was ever created
+        #[account(
+            mut,
+            seeds = [b"sol_vault".as_ref(), track.key().as_ref()],
+            bump,
+        )]
+        pub sol_vault: UncheckedAccount<'info>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub token_program: Program<'info, Token>,
+        pub token_2022_program: Program<'info, Token2022>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+    pub struct FeeRecipient {
+        pub mint: Pubkey,
+        pub recipient: Pubkey,
+    }
+
+    // Global program state. Fees go to the recipient's ATA for SPL mints and
+    // straight to the recipient wallet for the native mint (SOL vaults).
+    #[account]
+    #[derive(InitSpace)]
+    pub struct ProtocolConfig {
+        pub admin: Pubkey,
+        pub fee_bps: u16,
+        pub max_fee_bps: u16,
+
+        #[max_len(MAX_FEE_RECIPIENTS)]
+        pub fee_recipients: Vec<FeeRecipient>,
+        pub bump: u8,
+
+        // Guardian can halt every state-changing instruction in an emergency
+        pub guardian: Pubkey,
+        pub paused: bool,
+    }
+
+    impl ProtocolConfig {
+        pub fn recipient_for(&self, mint: &Pubkey) -> Option<Pubkey> {
+            self.fee_recipients
+                .iter()
+                .find(|entry| entry.mint == *mint)
+                .map(|entry| entry.recipient)
+        }
+    }
+
+    // Presence of this PDA exempts a partner track from protocol fees
+    #[account]
+    #[derive(InitSpace)]
+    pub struct FeeExemption {
+        pub track: Pubkey,
+        pub bump: u8,
+    }
+
+    #[event]
+    pub struct DepositHeld {
+        pub track_id: u64,
+        pub deposit_id: u64,
+        pub release_at: i64,
+    }
+
+    #[event]
+    pub struct DepositRefunded {
+        pub track_id: u64,
+        pub deposit_id: u64,
+        pub depositor: Pubkey,
+        pub amount: u64,
+    }
+
+    #[event]
+    pub struct DepositReleased {
+        pub track_id: u64,
+        pub deposit_id: u64,
+        pub amount: u64,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(amount: u64, track_id: u64, authority: Pubkey)]
+    pub struct EscrowDepositHeld<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(
+            init,
+            payer = payer,
+            space = 8 + Deposit::INIT_SPACE,
+            seeds = [
+                b"deposit".as_ref(),
+                track.key().as_ref(),
+                mint_registry.deposit_count.to_le_bytes().as_ref(),
+            ],
+            bump,
+        )]
+        pub deposit: Account<'info, Deposit>,
+
+        #[account(
+            mut,
+            token::mint = mint,
+            token::token_program = token_program,
+        )]
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mut)]
+        pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, deposit_id: u64)]
+    pub struct RefundDeposit<'info> {
+        pub caller: Signer<'info>,
+
+        // No pause checks: the hold window keeps running during a pause, so refunds stay open
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                track.authority.as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(
+            mut,
+            seeds = [b"deposit".as_ref(), track.key().as_ref(), deposit_id.to_le_bytes().as_ref()],
+            bump = deposit.bump,
+        )]
+        pub deposit: Account<'info, Deposit>,
+
+        #[account(
+            mut,
+            associated_token::mint = mint,
+            associated_token::authority = track,
+            associated_token::token_program = token_program,
+        )]
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(
+            mut,
+            token::mint = mint,
+            token::authority = deposit.depositor,
+            token::token_program = token_program,
+        )]
+        pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(
+            address = deposit.mint,
+            mint::token_program = token_program,
+        )]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, deposit_id: u64)]
+    pub struct ReleaseDeposit<'info> {
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                track.authority.as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(
+            mut,
+            seeds = [b"deposit".as_ref(), track.key().as_ref(), deposit_id.to_le_bytes().as_ref()],
+            bump = deposit.bump,
+        )]
+        pub deposit: Account<'info, Deposit>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+    pub struct AcceptedMint {
+        pub mint: Pubkey,
+        pub escrow: Pubkey,
+        pub held: u64,
+        // Claimable contributor balances still sitting in the escrow
+        pub credited_outstanding: u64,
+
+        // Lifetime accounting, net of refunds
+        pub total_deposited: u64,
+        pub total_distributed: u64,
+        pub total_protocol_fees: u64,
+        // Rounding remainder left in the escrow by distributions
+        pub dust_outstanding: u64,
+    }
+
+    impl AcceptedMint {
+        // Escrow balance that is neither held by a deposit window nor owed to claimants
+        pub fn available(&self, escrow_balance: u64) -> u64 {
+            escrow_balance
+                .saturating_sub(self.held)
+                .saturating_sub(self.credited_outstanding)
+        }
+
+        pub fn record_settlement(&mut self, settlement: &Settlement) -> Result<()> {
+            self.total_distributed = self.total_distributed.checked_add(settlement.distributed()?).ok_or(ErrorCode::MathError)?;
+            self.credited_outstanding = self.credited_outstanding.checked_add(settlement.credited).ok_or(ErrorCode::MathError)?;
+            self.total_protocol_fees = self.total_protocol_fees.checked_add(settlement.protocol_fee).ok_or(ErrorCode::MathError)?;
+            self.dust_outstanding = settlement.carry_dust(self.dust_outstanding)?;
+            Ok(())
+        }
+    }
+
+    // Currencies a track accepts, each with the escrow ATA owned by the track PDA
+    #[account]
+    #[derive(InitSpace)]
+    pub struct MintRegistry {
+        pub track: Pubkey,
+
+        #[max_len(MAX_ACCEPTED_MINTS)]
+        pub mints: Vec<AcceptedMint>,
+        pub bump: u8,
+        pub deposit_count: u64,
+    }
+
+    impl MintRegistry {
+        pub fn find(&self, mint: &Pubkey) -> Option<&AcceptedMint> {
+            self.mints.iter().find(|entry| entry.mint == *mint)
+        }
+
+        pub fn find_mut(&mut self, mint: &Pubkey) -> Option<&mut AcceptedMint> {
+            self.mints.iter_mut().find(|entry| entry.mint == *mint)
+        }
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+    pub enum DepositStatus {
+        Held,
+        Released,
+        Refunded,
+    }
+
+    // Ledger entry for a deposit made through escrow_deposit_held. `reference` is a
+    // Solana Pay style reference key and `memo` a DSP statement or invoice ID.
+    #[account]
+    #[derive(InitSpace)]
+    pub struct Deposit {
+        pub track: Pubkey,
+        pub depositor: Pubkey,
+        pub mint: Pubkey,
+        pub deposit_id: u64,
+        pub amount: u64,
+        pub release_at: i64,
+        pub status: DepositStatus,
+        pub bump: u8,
+        pub reference: Option<Pubkey>,
+
+        #[max_len(MAX_MEMO_LEN)]
+        pub memo: String,
+    }
+
+    #[event]
+    pub struct StemNFTMinted {
+        pub track_id: u64,
+        pub stem_index: u32,
+        pub mint: Pubkey,
+        pub recipient: Pubkey,
+        pub minted_by: Pubkey,
+    }
+
+
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct StemMintNFT<'info> {
+
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        // Track account - just verify it matches the track_id
+        // We don't use PDA seeds here because the track was created by a different authority
+        // The client must pass the correct track address
+        #[account(
+            constraint = track.track_id == track_id @ ErrorCode::InvalidArgs,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        #[account(
+            init,
+            payer = payer,
+            seeds = [
+                b"stem_mint".as_ref(),
+                stem.key().as_ref(),
+            ],
+            bump,
+            mint::decimals = 0,
+            mint::authority = track,
+            mint::token_program = token_program,
+        )]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            init,
+            payer = payer,
+            space = 8 + StemMintLink::INIT_SPACE,
+            seeds = [b"stem_link".as_ref(), mint.key().as_ref()],
+            bump,
+        )]
+        pub stem_mint_link: Account<'info, StemMintLink>,
+
+        ///CHECK: any wallet when the stem's creator mints, otherwise the creator or a listed contributor
+        pub recipient: UncheckedAccount<'info>,
+
+        #[account(
+            init_if_needed,
+            payer = payer,
+            associated_token::mint = mint,
+            associated_token::authority = recipient,
+            associated_token::token_program = token_program,
+        )]
+        pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        // The stem's creator, or the track authority airdropping on their behalf
+        pub authority: Signer<'info>,
+
+        ///CHECK: created by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), mint.key().as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub metadata: UncheckedAccount<'info>,
+
+        ///CHECK: created by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), mint.key().as_ref(), b"edition".as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub master_edition: UncheckedAccount<'info>,
+
+        // Required once the track has a collection; the metadata program checks them on verify
+        ///CHECK: must match track.collection_mint
+        pub collection_mint: Option<UncheckedAccount<'info>>,
+
+        ///CHECK: collection metadata, checked by the Token Metadata program
+        #[account(mut)]
+        pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+        ///CHECK: collection master edition, checked by the Token Metadata program
+        pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+        // Stem NFTs stay on SPL Token. Their 1-of-1 guarantee, creator royalties and collection
+        // membership all come from the Token Metadata master edition and sized collection
+        // instructions used here, which only take SPL Token mints. A Token-2022 mint with the
+        // metadata-pointer extension would carry none of them, so Token-2022 support covers
+        // escrows and royalty currencies only.
+        #[account(constraint = token_program.key() == Token::id() @ ErrorCode::StemNftRequiresSplToken)]
+        pub token_program: Interface<'info, TokenInterface>,
+        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+        pub token_metadata_program: Program<'info, Metadata>,
+        pub system_program: Program<'info, System>,
+        pub rent: Sysvar<'info, Rent>,
+
+    }
+
+    #[event]
+    pub struct TrackCollectionCreated {
+        pub track_id: u64,
+        pub collection_mint: Pubkey,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct CreateTrackCollection<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            init,
+            payer = authority,
+            seeds = [b"collection".as_ref(), track.key().as_ref()],
+            bump,
+            mint::decimals = 0,
+            mint::authority = track,
+            mint::token_program = token_program,
+        )]
+        pub collection_mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            init_if_needed,
+            payer = authority,
+            associated_token::mint = collection_mint,
+            associated_token::authority = authority,
+            associated_token::token_program = token_program,
+        )]
+        pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        ///CHECK: created by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub metadata: UncheckedAccount<'info>,
+
+        ///CHECK: created by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition".as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub master_edition: UncheckedAccount<'info>,
+
+        pub token_program: Program<'info, Token>,
+        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+        pub token_metadata_program: Program<'info, Metadata>,
+        pub system_program: Program<'info, System>,
+        pub rent: Sysvar<'info, Rent>,
+    }
+
+    #[event]
+    pub struct StemCollectionVerified {
+        pub track_id: u64,
+        pub stem_index: u32,
+        pub mint: Pubkey,
+        pub collection_mint: Pubkey,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct VerifyStemInCollection<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        ///CHECK: must match stem.mint
+        pub stem_mint: UncheckedAccount<'info>,
+
+        ///CHECK: stem metadata, updated by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), stem_mint.key().as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub metadata: UncheckedAccount<'info>,
+
+        ///CHECK: must match track.collection_mint
+        pub collection_mint: UncheckedAccount<'info>,
+
+        ///CHECK: collection metadata, checked by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub collection_metadata: UncheckedAccount<'info>,
+
+        ///CHECK: collection master edition, checked by the Token Metadata program
+        #[account(
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition".as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub collection_master_edition: UncheckedAccount<'info>,
+
+        pub token_metadata_program: Program<'info, Metadata>,
+    }
+
+
+    #[derive(Accounts)]
+    #[instruction(amount: u64, track_id: u64)]
+    pub struct EscrowDistribute<'info> {
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            token::mint = mint,
+            token::token_program = token_program,
+        )]
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        ///CHECK: This is the PDA authority for the track
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump
+        )]
+        pub track_authority: UncheckedAccount<'info>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            seeds = [b"fee_exemption".as_ref(), track.key().as_ref()],
+            bump = fee_exemption.bump,
+        )]
+        pub fee_exemption: Option<Account<'info, FeeExemption>>,
+
+        // Pays rent for contributor earnings records created on first payout
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+    }
+
+
+
+    #[event]
+    pub struct EscrowDeposited {
+        pub track_id: u64,
+        pub depositor: Pubkey,
+        pub amount: u64,
+        pub mint: Pubkey,
+        pub transfer_fee: u64,
+        pub deposit_id: Option<u64>,
+        pub reference: Option<Pubkey>,
+        pub memo: Option<String>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(amount: u64, track_id: u64, authority: Pubkey)]
+    pub struct EscrowDeposit<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(
+            mut,
+            token::mint = mint,
+            token::token_program = token_program,
+        )]
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mut)]
+        pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        // Ledger entry, required when the deposit carries a reference or memo
+        #[account(
+            init,
+            payer = payer,
+            space = 8 + Deposit::INIT_SPACE,
+            seeds = [
+                b"deposit".as_ref(),
+                track.key().as_ref(),
+                mint_registry.deposit_count.to_le_bytes().as_ref(),
+            ],
+            bump,
+        )]
+        pub deposit: Option<Account<'info, Deposit>>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+
+    }
+
+
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, authority: Pubkey)]
+    pub struct CreateEscrowAta<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        ///CHECK: ATA for mint
+        #[account(mut)]
+        pub escrow_token_account: UncheckedAccount<'info>,
+
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+
+    }
+
+    // Removed CreateEscrowAta struct - no longer needed for SOL-based escrow
+
+    #[event]
+    pub struct SharesUpdated {
+        pub track_id: u64,
+        pub new_shares: Vec<u16>,
+        pub old_version: u32,
+        pub new_version: u32,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, new_shares_bps: Vec<u16>, contributors: Vec<Pubkey>)]
+    pub struct UpdateShares<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+            realloc = Track::space(track.title.len(), track.cid.len(), contributors.len(), track.stem_mints.len(), track.royalty_stems.len()),
+            realloc::payer = authority,
+            realloc::zero = false,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct StemMint<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(), 
+                authority.key().as_ref(), 
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        #[account(
+            mut,
+            mint::decimals = 0,
+            mint::token_program = token_program,
+        )]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            init,
+            payer = authority,
+            space = 8 + StemMintLink::INIT_SPACE,
+            seeds = [b"stem_link".as_ref(), mint.key().as_ref()],
+            bump,
+        )]
+        pub stem_mint_link: Account<'info, StemMintLink>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+    }
+
+    // Reverse lookup from a mint to the one stem it is linked to
+    #[account]
+    #[derive(InitSpace)]
+    pub struct StemMintLink {
+        pub mint: Pubkey,
+        pub track: Pubkey,
+        pub stem_index: u32,
+        pub bump: u8,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+    pub enum StemType {
+        Drums,
+        Bass,
+        Vocals,
+        Keys,
+        Guitar,
+        Synth,
+        Fx,
+        Other,
+    }
+
+    impl StemType {
+        pub fn label(&self) -> &'static str {
+            match self {
+                StemType::Drums => "Drums",
+                StemType::Bass => "Bass",
+                StemType::Vocals => "Vocals",
+                StemType::Keys => "Keys",
+                StemType::Guitar => "Guitar",
+                StemType::Synth => "Synth",
+                StemType::Fx => "FX",
+                StemType::Other => "Stem",
+            }
+        }
+    }
+
+    // One stem of a track, indexed in creation order
+    #[account]
+    #[derive(InitSpace)]
+    pub struct Stem {
+        pub track: Pubkey,
+        pub stem_index: u32,
+        pub stem_type: StemType,
+
+        #[max_len(MAX_CID_LEN)]
+        pub cid: String,
+        pub audio_hash: [u8; 32],
+        pub creator: Pubkey,
+        pub mint: Option<Pubkey>,
+        pub retired: bool,
+        pub bump: u8,
+    }
+
+    #[event]
+    pub struct StemAdded {
+        pub track: Pubkey,
+        pub stem_index: u32,
+        pub stem_type: StemType,
+        pub creator: Pubkey,
+    }
+
+    #[event]
+    pub struct StemUpdated {
+        pub track: Pubkey,
+        pub stem_index: u32,
+    }
+
+    #[event]
+    pub struct StemRetired {
+        pub track: Pubkey,
+        pub stem_index: u32,
+    }
+
+    #[event]
+    pub struct StemMintLinked {
+        pub track: Pubkey,
+        pub stem_index: u32,
+        pub mint: Pubkey,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct AddStem<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            init,
+            payer = authority,
+            space = 8 + Stem::INIT_SPACE,
+            seeds = [b"stem".as_ref(), track.key().as_ref(), track.stem_count.to_le_bytes().as_ref()],
+            bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct UpdateStem<'info> {
+        pub authority: Signer<'info>,
+
+        #[account(
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            mut,
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+    }
+
+    // Shrinks a track created before accounts were right-sized, refunding the spare rent
+    #[derive(Accounts)]
+    #[instruction(track_id: u64)]
+    pub struct ResizeTrack<'info> {
+        #[account(mut)]
+        pub authority: Signer<'info>,
+
+        #[account(
+            mut,
+            seeds = [
+                b"track".as_ref(),
+                authority.key().as_ref(),
+                track_id.to_le_bytes().as_ref()
+                ],
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+            realloc = track.size(),
+            realloc::payer = authority,
+            realloc::zero = false,
+        )]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+
+        pub system_program: Program<'info, System>,
+    }
+
+    #[event]
+    pub struct TrackInitialized {
+        pub track_id: u64,
+        pub authority: Pubkey,
+        pub contributors: Vec<Pubkey>,
+        pub shares: Vec<u16>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(title: String, cid: String, master_hash: [u8; 32], contributors: Vec<Pubkey>)]
+    pub struct InitializeTrack<'info>{
+
+        #[account(mut)]
+        pub authority: Signer<'info>,
 
         #[account(
-            mut,
-            seeds = [
-                b"track".as_ref(), 
-                authority.key().as_ref(), 
-                track_id.to_le_bytes().as_ref()
-                ],
+            init_if_needed,
+            payer = authority,
+            space = 8 + ArtistCounter::INIT_SPACE,
+            seeds = [b"artist_counter".as_ref(), authority.key().as_ref()],
+            bump,
+        )]
+        pub artist_counter: Account<'info, ArtistCounter>,
+
+        #[account(
+            init,
+            payer = authority,
+            space = Track::space(title.len(), cid.len(), contributors.len(), 0, 0),
+            seeds = [b"track".as_ref(), authority.key().as_ref(), artist_counter.track_count.to_le_bytes().as_ref()],
             bump,
         )]
         pub track: Account<'info, Track>,
 
-        #[account(mut)]
-        pub escrow_token_account: Account<'info, TokenAccount>,
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
+        
+        pub system_program: Program<'info, System>,
+    }
+
+    #[event]
+    pub struct ArtistCounterInitialized {
+        pub authority: Pubkey,
+        pub track_count: u64,
+    }
 
+    #[derive(Accounts)]
+    pub struct InitArtistCounter<'info> {
         #[account(mut)]
-        pub payer_token_account: Account<'info, TokenAccount>,
+        pub authority: Signer<'info>,
+
+        #[account(
+            init,
+            payer = authority,
+            space = 8 + ArtistCounter::INIT_SPACE,
+            seeds = [b"artist_counter".as_ref(), authority.key().as_ref()],
+            bump,
+        )]
+        pub artist_counter: Account<'info, ArtistCounter>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
 
+        pub system_program: Program<'info, System>,
+    }
 
-        pub token_program: Program<'info, Token>,
+    // Next track id for an authority. Ids below track_count were handed out once, by
+    // initialize_track or, below a seeded start, to legacy tracks, and are never reused. A
+    // track closed by close_track leaves its id empty, so clients enumerating 0..track_count
+    // skip ids whose PDA no longer exists.
+    #[account]
+    #[derive(InitSpace)]
+    pub struct ArtistCounter {
+        pub authority: Pubkey,
+        pub track_count: u64,
+        pub bump: u8,
+    }
+
+    #[account]
+    #[derive(InitSpace)]
+    pub struct Track {
+        pub authority: Pubkey,
+        pub track_id: u64,
+
+        #[max_len(MAX_TITLE_LEN)]
+        pub title: String,
+
+        #[max_len(MAX_CID_LEN)]
+        pub cid: String,
+        pub master_hash: [u8; 32],
+
+        #[max_len(MAX_CONTRIBUTORS)]
+        pub contributors: Vec<Pubkey>,
+
+        #[max_len(MAX_CONTRIBUTORS)]
+        pub shares: Vec<u16>,
+
+        // Legacy: stem NFT mints issued before each Stem account recorded its own mint. No
+        // longer written; read `Stem.mint` instead.
+        #[max_len(MAX_STEMS)]
+        pub stem_mints: Vec<Pubkey>,
+        pub royalty_version: u32,
+        pub bump: u8,
+        pub paused: bool,
+        pub catalog: Option<Pubkey>,
+        pub stem_count: u32,
+        // Collection NFT every stem NFT is verified into, once created
+        pub collection_mint: Option<Pubkey>,
+
+        // Stem royalty positions, paid to the stem NFT holder. Their bps sit beside `shares`
+        // and the two together make up 10000.
+        #[max_len(MAX_CONTRIBUTORS)]
+        pub royalty_stems: Vec<StemRoyalty>,
+
+        // Label split in force when the track joined its catalog; caps the label cut
+        pub catalog_share_cap: u16,
+    }
+
+    impl Track {
+        // Account size for the given content, discriminator included. Tracks are sized to
+        // what they hold and grown with realloc when contributors or stems are added.
+        pub fn space(title_len: usize, cid_len: usize, contributors: usize, stems: usize, royalty_stems: usize) -> usize {
+            8 + 32 + 8
+                + 4 + title_len
+                + 4 + cid_len
+                + 32
+                + 4 + contributors * 32
+                + 4 + contributors * 2
+                + 4 + stems * 32
+                + 4 + 1 + 1
+                + 1 + 32
+                + 4
+                + 1 + 32
+                + 4 + royalty_stems * StemRoyalty::INIT_SPACE
+                + 2
+        }
+
+        pub fn size(&self) -> usize {
+            Track::space(
+                self.title.len(),
+                self.cid.len(),
+                self.contributors.len(),
+                self.stem_mints.len(),
+                self.royalty_stems.len(),
+            )
+        }
+
+        pub fn royalty_bps(&self) -> u64 {
+            self.royalty_stems.iter().map(|royalty| royalty.share_bps as u64).sum()
+        }
+
+        pub fn is_royalty_stem(&self, key: &Pubkey) -> bool {
+            self.royalty_stems.iter().any(|royalty| royalty.stem == *key)
+        }
+
+        // Every position in the split with its bps: contributors, then stem royalty positions
+        pub fn payees(&self) -> Vec<(Pubkey, u16)> {
+            self.contributors
+                .iter()
+                .copied()
+                .zip(self.shares.iter().copied())
+                .chain(self.royalty_stems.iter().map(|royalty| (royalty.stem, royalty.share_bps)))
+                .collect()
+        }
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+    pub struct StemRoyalty {
+        // Stem PDA; its earnings record is keyed by it
+        pub stem: Pubkey,
+        pub share_bps: u16,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+    pub enum ContributorRole {
+        Artist,
+        Producer,
+        Songwriter,
+        Engineer,
+        Featured,
+        Other,
+    }
 
+    impl ContributorRole {
+        pub fn label(&self) -> &'static str {
+            match self {
+                ContributorRole::Artist => "Artist",
+                ContributorRole::Producer => "Producer",
+                ContributorRole::Songwriter => "Songwriter",
+                ContributorRole::Engineer => "Engineer",
+                ContributorRole::Featured => "Featured",
+                ContributorRole::Other => "Contributor",
+            }
+        }
     }
 
+    // Non-transferable credit for one contributor on a track
+    #[account]
+    #[derive(InitSpace)]
+    pub struct ContributorBadge {
+        pub track: Pubkey,
+        pub contributor: Pubkey,
+        pub role: ContributorRole,
+        pub mint: Pubkey,
+        pub bump: u8,
+    }
 
+    #[event]
+    pub struct ContributorBadgeMinted {
+        pub track_id: u64,
+        pub contributor: Pubkey,
+        pub role: ContributorRole,
+        pub mint: Pubkey,
+    }
 
     #[derive(Accounts)]
-    #[instruction(track_id: u64, authority: Pubkey)]
-    pub struct CreateEscrowAta<'info> {
+    #[instruction(track_id: u64)]
+    pub struct MintContributorBadge<'info> {
         #[account(mut)]
-        pub payer: Signer<'info>,
+        pub authority: Signer<'info>,
 
         #[account(
-            mut,
             seeds = [
-                b"track".as_ref(), 
-                authority.key().as_ref(), 
+                b"track".as_ref(),
+                authority.key().as_ref(),
                 track_id.to_le_bytes().as_ref()
                 ],
-            bump,
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
         )]
         pub track: Account<'info, Track>,
 
-        ///CHECK: ATA for mint
-        #[account(mut)]
-        pub escrow_token_account: UncheckedAccount<'info>,
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
 
-        pub mint: Account<'info, Mint>,
+        ///CHECK: must be one of track.contributors
+        pub contributor: UncheckedAccount<'info>,
+
+        // One badge per contributor per track
+        #[account(
+            init,
+            payer = authority,
+            space = 8 + ContributorBadge::INIT_SPACE,
+            seeds = [b"badge".as_ref(), track.key().as_ref(), contributor.key().as_ref()],
+            bump,
+        )]
+        pub badge: Account<'info, ContributorBadge>,
+
+        #[account(
+            init,
+            payer = authority,
+            seeds = [b"badge_mint".as_ref(), badge.key().as_ref()],
+            bump,
+            mint::decimals = 0,
+            mint::authority = track,
+            mint::freeze_authority = track,
+            mint::token_program = token_program,
+        )]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(
+            init_if_needed,
+            payer = authority,
+            associated_token::mint = mint,
+            associated_token::authority = contributor,
+            associated_token::token_program = token_program,
+        )]
+        pub contributor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        ///CHECK: created by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), mint.key().as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub metadata: UncheckedAccount<'info>,
+
+        ///CHECK: created by the Token Metadata program
+        #[account(
+            mut,
+            seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), mint.key().as_ref(), b"edition".as_ref()],
+            bump,
+            seeds::program = token_metadata_program.key(),
+        )]
+        pub master_edition: UncheckedAccount<'info>,
 
-        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
         pub token_program: Program<'info, Token>,
+        pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+        pub token_metadata_program: Program<'info, Metadata>,
         pub system_program: Program<'info, System>,
-
+        pub rent: Sysvar<'info, Rent>,
     }
 
-    // Removed CreateEscrowAta struct - no longer needed for SOL-based escrow
+    #[event]
+    pub struct StemRoyaltyAttached {
+        pub track_id: u64,
+        pub stem_index: u32,
+        pub stem: Pubkey,
+        pub mint: Pubkey,
+        pub share_bps: u16,
+    }
 
     #[event]
-    pub struct SharesUpdated {
+    pub struct StemRoyaltyDetached {
         pub track_id: u64,
-        pub new_shares: Vec<u16>,
-        pub old_version: u32,
-        pub new_version: u32,
+        pub stem_index: u32,
+        pub stem: Pubkey,
+        pub holder: Pubkey,
+        pub recipient: Pubkey,
+        pub share_bps: u16,
+    }
+
+    #[event]
+    pub struct StemRoyaltyPaid {
+        pub track: Pubkey,
+        pub stem: Pubkey,
+        pub holder: Pubkey,
+        pub mint: Pubkey,
+        pub amount: u64,
     }
 
     #[derive(Accounts)]
-    #[instruction(track_id: u64)]
-    pub struct UpdateShares<'info> {
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct AttachStemRoyalty<'info> {
         #[account(mut)]
         pub authority: Signer<'info>,
 
         #[account(
             mut,
             seeds = [
-                b"track".as_ref(), 
-                authority.key().as_ref(), 
+                b"track".as_ref(),
+                authority.key().as_ref(),
                 track_id.to_le_bytes().as_ref()
                 ],
-            bump,
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
+            realloc = Track::space(track.title.len(), track.cid.len(), track.contributors.len(), track.stem_mints.len(), track.royalty_stems.len() + 1),
+            realloc::payer = authority,
+            realloc::zero = false,
         )]
         pub track: Account<'info, Track>,
-    }
 
+        #[account(
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        // The share comes out of the stem creator's split, so they agree to it
+        #[account(constraint = creator.key() == stem.creator @ ErrorCode::Unauthorized)]
+        pub creator: Signer<'info>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
 
+        pub system_program: Program<'info, System>,
+    }
 
     #[derive(Accounts)]
-    #[instruction(track_id: u64)]
-    pub struct StemMint<'info> {
-        #[account(mut)]
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct DetachStemRoyalty<'info> {
         pub authority: Signer<'info>,
 
         #[account(
             mut,
             seeds = [
-                b"track".as_ref(), 
-                authority.key().as_ref(), 
+                b"track".as_ref(),
+                authority.key().as_ref(),
                 track_id.to_le_bytes().as_ref()
                 ],
-            bump,
+            bump = track.bump,
+            has_one = authority,
+            constraint = !track.paused @ ErrorCode::TrackPaused,
         )]
         pub track: Account<'info, Track>,
-    }
 
-    #[event]
-    pub struct TrackInitialized {
-        pub track_id: u64,
-        pub authority: Pubkey,
-        pub contributors: Vec<Pubkey>,
-        pub shares: Vec<u16>,
+        #[account(
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        pub holder: Signer<'info>,
+
+        // Proves the holder signing away the position holds the stem NFT
+        #[account(
+            constraint = stem.mint == Some(stem_nft_account.mint) @ ErrorCode::NotStemHolder,
+            constraint = stem_nft_account.owner == holder.key() @ ErrorCode::NotStemHolder,
+            constraint = stem_nft_account.amount == 1 @ ErrorCode::NotStemHolder,
+        )]
+        pub stem_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(
+            seeds = [b"protocol_config".as_ref()],
+            bump = protocol_config.bump,
+            constraint = !protocol_config.paused @ ErrorCode::ProtocolPaused,
+        )]
+        pub protocol_config: Account<'info, ProtocolConfig>,
     }
 
     #[derive(Accounts)]
-    #[instruction(track_id: u64)]
-    pub struct InitializeTrack<'info>{
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct ClaimStemRoyalty<'info> {
+        pub holder: Signer<'info>,
+
+        #[account(constraint = track.track_id == track_id @ ErrorCode::InvalidArgs)]
+        pub track: Account<'info, Track>,
+
+        #[account(
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
+
+        // Proves the signer holds the stem NFT
+        #[account(
+            constraint = stem.mint == Some(stem_nft_account.mint) @ ErrorCode::NotStemHolder,
+            constraint = stem_nft_account.owner == holder.key() @ ErrorCode::NotStemHolder,
+            constraint = stem_nft_account.amount == 1 @ ErrorCode::NotStemHolder,
+        )]
+        pub stem_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(
+            mut,
+            seeds = [b"mint_registry".as_ref(), track.key().as_ref()],
+            bump = mint_registry.bump,
+        )]
+        pub mint_registry: Account<'info, MintRegistry>,
+
+        #[account(
+            mut,
+            seeds = [b"earnings".as_ref(), track.key().as_ref(), stem.key().as_ref()],
+            bump = earnings.bump,
+        )]
+        pub earnings: Account<'info, ContributorEarnings>,
 
         #[account(mut)]
-        pub authority: Signer<'info>,
+        pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
 
         #[account(
-            init,
-            payer = authority,
-            space = Track::INIT_SPACE,
-            seeds = [b"track".as_ref(), authority.key().as_ref(), &track_id.to_le_bytes().as_ref()],
-            bump,
+            mut,
+            token::mint = mint,
+            token::authority = holder,
+            token::token_program = token_program,
         )]
-        pub track: Account<'info, Track>,
-        
-        pub system_program: Program<'info, System>,
+        pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+
+        #[account(mint::token_program = token_program)]
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        pub token_program: Interface<'info, TokenInterface>,
     }
 
-    #[account]
-    #[derive(InitSpace)]
-    pub struct Track {
-        pub authority: Pubkey,
-        pub track_id: u64,
+    #[derive(Accounts)]
+    #[instruction(track_id: u64, stem_index: u32)]
+    pub struct ClaimStemRoyaltySol<'info> {
+        #[account(mut)]
+        pub holder: Signer<'info>,
 
-        #[max_len(MAX_TITLE_LEN)]
-        pub title: String,
+        #[account(constraint = track.track_id == track_id @ ErrorCode::InvalidArgs)]
+        pub track: Account<'info, Track>,
 
-        #[max_len(MAX_CID_LEN)]
-        pub cid: String,
-        pub master_hash: [u8; 32],
+        #[account(
+            seeds = [b"stem".as_ref(), track.key().as_ref(), stem_index.to_le_bytes().as_ref()],
+            bump = stem.bump,
+        )]
+        pub stem: Account<'info, Stem>,
 
-        #[max_len(MAX_CONTRIBUTORS * 32)]
-        pub contributors: Vec<Pubkey>,
+        // Proves the signer holds the stem NFT
+        #[account(
+            constraint = stem.mint == Some(stem_nft_account.mint) @ ErrorCode::NotStemHolder,
+            constraint = stem_nft_account.owner == holder.key() @ ErrorCode::NotStemHolder,
+            constraint = stem_nft_account.amount == 1 @ ErrorCode::NotStemHolder,
+        )]
+        pub stem_nft_account: InterfaceAccount<'info, TokenAccount>,
 
-        #[max_len(MAX_CONTRIBUTORS * 2)]
-        pub shares: Vec<u16>,
+        #[account(
+            mut,
+            seeds = [b"sol_vault".as_ref(), track.key().as_ref()],
+            bump = sol_vault.bump,
+        )]
+        pub sol_vault: Account<'info, SolVault>,
 
-        #[max_len(64 * 32)]
-        pub stem_mints: Vec<Pubkey>,
-        pub royalty_version: u32,
-        pub bump: u8,
+        #[account(
+            mut,
+            seeds = [b"earnings".as_ref(), track.key().as_ref(), stem.key().as_ref()],
+            bump = earnings.bump,
+        )]
+        pub earnings: Account<'info, ContributorEarnings>,
     }
 
     #[error_code]
@@ -552,4 +5874,108 @@ pub enum ErrorCode {
     InvalidRecipientCount,
     #[msg("The signer is not a contributor to this track")]
     NotAContributor,
+    #[msg("Mint is not in the track's accepted mint registry")]
+    MintNotAccepted,
+    #[msg("Mint is already accepted by this track")]
+    MintAlreadyAccepted,
+    #[msg("Too many accepted mints")]
+    TooManyAcceptedMints,
+    #[msg("Escrow must be empty")]
+    EscrowNotEmpty,
+    #[msg("A registered escrow account was not provided")]
+    MissingEscrowAccount,
+    #[msg("Vault balance above rent exemption is insufficient")]
+    InsufficientVaultBalance,
+    #[msg("Fee bps must not exceed 10000")]
+    InvalidFeeBps,
+    #[msg("Fee exceeds the configured maximum")]
+    FeeAboveMax,
+    #[msg("Too many fee recipients")]
+    TooManyFeeRecipients,
+    #[msg("Protocol fee recipient account was not provided")]
+    MissingFeeAccount,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Track is paused")]
+    TrackPaused,
+    #[msg("Hold period must be between 0 and 30 days")]
+    InvalidHoldPeriod,
+    #[msg("Deposit is not held")]
+    DepositNotHeld,
+    #[msg("Hold period has elapsed")]
+    HoldPeriodElapsed,
+    #[msg("Hold period is still active")]
+    HoldPeriodActive,
+    #[msg("Amount exceeds the escrow balance available for distribution")]
+    InsufficientEscrowBalance,
+    #[msg("Memo exceeds maximum length")]
+    MemoTooLong,
+    #[msg("Contributor earnings account was not provided")]
+    MissingEarningsAccount,
+    #[msg("Display name exceeds maximum length")]
+    DisplayNameTooLong,
+    #[msg("Profile already lists the maximum number of tracks")]
+    TooManyProfileTracks,
+    #[msg("Too many tracks for one distribution run")]
+    TooManyRunTracks,
+    #[msg("Distribution run has already settled every track")]
+    DistributionRunComplete,
+    #[msg("No track could be settled with the accounts provided")]
+    DistributionRunStalled,
+    #[msg("Label share must be at most 10000 bps")]
+    InvalidLabelShare,
+    #[msg("Catalog already has the maximum number of admins")]
+    TooManyCatalogAdmins,
+    #[msg("Track already belongs to a catalog")]
+    TrackAlreadyInCatalog,
+    #[msg("Track does not belong to this catalog")]
+    TrackNotInCatalog,
+    #[msg("Catalog account or label cut recipient was not provided")]
+    MissingCatalogAccount,
+    #[msg("UPC must be 12 or 13 digits")]
+    InvalidUpc,
+    #[msg("Too many tracks in release")]
+    TooManyReleaseTracks,
+    #[msg("Release member track, registry or escrow was not provided")]
+    MissingReleaseTrackAccount,
+    #[msg("Remove the track from its catalog before closing it")]
+    TrackInCatalog,
+    #[msg("Stem has been retired")]
+    StemRetired,
+    #[msg("Stem mint must have 0 decimals, a supply of one and no mint authority outside the track or its master edition")]
+    InvalidStemMint,
+    #[msg("Stem already has a linked mint")]
+    StemMintAlreadyLinked,
+    #[msg("Stem NFT metadata supports at most 5 creators")]
+    TooManyCreators,
+    #[msg("Track already has a collection NFT")]
+    CollectionAlreadyCreated,
+    #[msg("Track collection accounts were not provided")]
+    MissingCollectionAccount,
+    #[msg("Stem has no NFT to attach a royalty to")]
+    StemNotMinted,
+    #[msg("Stem already holds a share of the split")]
+    StemRoyaltyAlreadyAttached,
+    #[msg("Stem does not hold a share of the split")]
+    StemRoyaltyNotAttached,
+    #[msg("Royalty share must be positive and within the creator's share")]
+    InvalidRoyaltyShare,
+    #[msg("Stem account or stem NFT holder account was not provided")]
+    MissingStemHolderAccount,
+    #[msg("Signer does not hold the stem NFT")]
+    NotStemHolder,
+    #[msg("Deposits with a reference or memo need a ledger entry")]
+    MissingDepositRecord,
+    #[msg("Profile account of a removed contributor was not provided")]
+    MissingProfileAccount,
+    #[msg("Release member is not a track account or was not provided")]
+    InvalidReleaseTrack,
+    #[msg("Track is listed more than once in the release")]
+    DuplicateReleaseTrack,
+    #[msg("Held deposits or unclaimed earnings are still outstanding")]
+    OutstandingBalances,
+    #[msg("Stem NFTs are minted with the SPL Token program; Token-2022 master editions are not supported")]
+    StemNftRequiresSplToken,
 }
\ No newline at end of file
@@ -25,8 +25,6 @@ import {
   getU16Encoder,
   getU32Decoder,
   getU32Encoder,
-  getU64Decoder,
-  getU64Encoder,
   getUtf8Decoder,
   getUtf8Encoder,
   transformEncoder,
@@ -48,7 +46,6 @@ import {
 import { MUZICA_PROGRAM_ADDRESS } from '../programs';
 import {
   expectAddress,
-  expectSome,
   getAccountMetaFactory,
   type ResolvedAccount,
 } from '../shared';
@@ -66,7 +63,9 @@ export function getInitializeTrackDiscriminatorBytes() {
 export type InitializeTrackInstruction<
   TProgram extends string = typeof MUZICA_PROGRAM_ADDRESS,
   TAccountAuthority extends string | AccountMeta<string> = string,
+  TAccountArtistCounter extends string | AccountMeta<string> = string,
   TAccountTrack extends string | AccountMeta<string> = string,
+  TAccountProtocolConfig extends string | AccountMeta<string> = string,
   TAccountSystemProgram extends string | AccountMeta<string> =
     '11111111111111111111111111111111',
   TRemainingAccounts extends readonly AccountMeta<string>[] = [],
@@ -78,9 +77,15 @@ export type InitializeTrackInstruction<
         ? WritableSignerAccount<TAccountAuthority> &
             AccountSignerMeta<TAccountAuthority>
         : TAccountAuthority,
+      TAccountArtistCounter extends string
+        ? WritableAccount<TAccountArtistCounter>
+        : TAccountArtistCounter,
       TAccountTrack extends string
         ? WritableAccount<TAccountTrack>
         : TAccountTrack,
+      TAccountProtocolConfig extends string
+        ? ReadonlyAccount<TAccountProtocolConfig>
+        : TAccountProtocolConfig,
       TAccountSystemProgram extends string
         ? ReadonlyAccount<TAccountSystemProgram>
         : TAccountSystemProgram,
@@ -90,7 +95,6 @@ export type InitializeTrackInstruction<
 
 export type InitializeTrackInstructionData = {
   discriminator: ReadonlyUint8Array;
-  trackId: bigint;
   title: string;
   cid: string;
   masterHash: ReadonlyUint8Array;
@@ -99,7 +103,6 @@ export type InitializeTrackInstructionData = {
 };
 
 export type InitializeTrackInstructionDataArgs = {
-  trackId: number | bigint;
   title: string;
   cid: string;
   masterHash: ReadonlyUint8Array;
@@ -111,7 +114,6 @@ export function getInitializeTrackInstructionDataEncoder(): Encoder<InitializeTr
   return transformEncoder(
     getStructEncoder([
       ['discriminator', fixEncoderSize(getBytesEncoder(), 8)],
-      ['trackId', getU64Encoder()],
       ['title', addEncoderSizePrefix(getUtf8Encoder(), getU32Encoder())],
       ['cid', addEncoderSizePrefix(getUtf8Encoder(), getU32Encoder())],
       ['masterHash', fixEncoderSize(getBytesEncoder(), 32)],
@@ -125,7 +127,6 @@ export function getInitializeTrackInstructionDataEncoder(): Encoder<InitializeTr
 export function getInitializeTrackInstructionDataDecoder(): Decoder<InitializeTrackInstructionData> {
   return getStructDecoder([
     ['discriminator', fixDecoderSize(getBytesDecoder(), 8)],
-    ['trackId', getU64Decoder()],
     ['title', addDecoderSizePrefix(getUtf8Decoder(), getU32Decoder())],
     ['cid', addDecoderSizePrefix(getUtf8Decoder(), getU32Decoder())],
     ['masterHash', fixDecoderSize(getBytesDecoder(), 32)],
@@ -146,13 +147,16 @@ export function getInitializeTrackInstructionDataCodec(): Codec<
 
 export type InitializeTrackAsyncInput<
   TAccountAuthority extends string = string,
+  TAccountArtistCounter extends string = string,
   TAccountTrack extends string = string,
+  TAccountProtocolConfig extends string = string,
   TAccountSystemProgram extends string = string,
 > = {
   authority: TransactionSigner<TAccountAuthority>;
-  track?: Address<TAccountTrack>;
+  artistCounter?: Address<TAccountArtistCounter>;
+  track: Address<TAccountTrack>;
+  protocolConfig?: Address<TAccountProtocolConfig>;
   systemProgram?: Address<TAccountSystemProgram>;
-  trackId: InitializeTrackInstructionDataArgs['trackId'];
   title: InitializeTrackInstructionDataArgs['title'];
   cid: InitializeTrackInstructionDataArgs['cid'];
   masterHash: InitializeTrackInstructionDataArgs['masterHash'];
@@ -162,13 +166,17 @@ export type InitializeTrackAsyncInput<
 
 export async function getInitializeTrackInstructionAsync<
   TAccountAuthority extends string,
+  TAccountArtistCounter extends string,
   TAccountTrack extends string,
+  TAccountProtocolConfig extends string,
   TAccountSystemProgram extends string,
   TProgramAddress extends Address = typeof MUZICA_PROGRAM_ADDRESS,
 >(
   input: InitializeTrackAsyncInput<
     TAccountAuthority,
+    TAccountArtistCounter,
     TAccountTrack,
+    TAccountProtocolConfig,
     TAccountSystemProgram
   >,
   config?: { programAddress?: TProgramAddress }
@@ -176,7 +184,9 @@ export async function getInitializeTrackInstructionAsync<
   InitializeTrackInstruction<
     TProgramAddress,
     TAccountAuthority,
+    TAccountArtistCounter,
     TAccountTrack,
+    TAccountProtocolConfig,
     TAccountSystemProgram
   >
 > {
@@ -186,7 +196,9 @@ export async function getInitializeTrackInstructionAsync<
   // Original accounts.
   const originalAccounts = {
     authority: { value: input.authority ?? null, isWritable: true },
+    artistCounter: { value: input.artistCounter ?? null, isWritable: true },
     track: { value: input.track ?? null, isWritable: true },
+    protocolConfig: { value: input.protocolConfig ?? null, isWritable: false },
     systemProgram: { value: input.systemProgram ?? null, isWritable: false },
   };
   const accounts = originalAccounts as Record<
@@ -198,13 +210,29 @@ export async function getInitializeTrackInstructionAsync<
   const args = { ...input };
 
   // Resolve default values.
-  if (!accounts.track.value) {
-    accounts.track.value = await getProgramDerivedAddress({
+  if (!accounts.artistCounter.value) {
+    accounts.artistCounter.value = await getProgramDerivedAddress({
       programAddress,
       seeds: [
-        getBytesEncoder().encode(new Uint8Array([116, 114, 97, 99, 107])),
+        getBytesEncoder().encode(
+          new Uint8Array([
+            97, 114, 116, 105, 115, 116, 95, 99, 111, 117, 110, 116, 101, 114,
+          ])
+        ),
         getAddressEncoder().encode(expectAddress(accounts.authority.value)),
-        getU64Encoder().encode(expectSome(args.trackId)),
+      ],
+    });
+  }
+  if (!accounts.protocolConfig.value) {
+    accounts.protocolConfig.value = await getProgramDerivedAddress({
+      programAddress,
+      seeds: [
+        getBytesEncoder().encode(
+          new Uint8Array([
+            112, 114, 111, 116, 111, 99, 111, 108, 95, 99, 111, 110, 102, 105,
+            103,
+          ])
+        ),
       ],
     });
   }
@@ -217,7 +245,9 @@ export async function getInitializeTrackInstructionAsync<
   return Object.freeze({
     accounts: [
       getAccountMeta(accounts.authority),
+      getAccountMeta(accounts.artistCounter),
       getAccountMeta(accounts.track),
+      getAccountMeta(accounts.protocolConfig),
       getAccountMeta(accounts.systemProgram),
     ],
     data: getInitializeTrackInstructionDataEncoder().encode(
@@ -227,20 +257,25 @@ export async function getInitializeTrackInstructionAsync<
   } as InitializeTrackInstruction<
     TProgramAddress,
     TAccountAuthority,
+    TAccountArtistCounter,
     TAccountTrack,
+    TAccountProtocolConfig,
     TAccountSystemProgram
   >);
 }
 
 export type InitializeTrackInput<
   TAccountAuthority extends string = string,
+  TAccountArtistCounter extends string = string,
   TAccountTrack extends string = string,
+  TAccountProtocolConfig extends string = string,
   TAccountSystemProgram extends string = string,
 > = {
   authority: TransactionSigner<TAccountAuthority>;
+  artistCounter: Address<TAccountArtistCounter>;
   track: Address<TAccountTrack>;
+  protocolConfig: Address<TAccountProtocolConfig>;
   systemProgram?: Address<TAccountSystemProgram>;
-  trackId: InitializeTrackInstructionDataArgs['trackId'];
   title: InitializeTrackInstructionDataArgs['title'];
   cid: InitializeTrackInstructionDataArgs['cid'];
   masterHash: InitializeTrackInstructionDataArgs['masterHash'];
@@ -250,20 +285,26 @@ export type InitializeTrackInput<
 
 export function getInitializeTrackInstruction<
   TAccountAuthority extends string,
+  TAccountArtistCounter extends string,
   TAccountTrack extends string,
+  TAccountProtocolConfig extends string,
   TAccountSystemProgram extends string,
   TProgramAddress extends Address = typeof MUZICA_PROGRAM_ADDRESS,
 >(
   input: InitializeTrackInput<
     TAccountAuthority,
+    TAccountArtistCounter,
     TAccountTrack,
+    TAccountProtocolConfig,
     TAccountSystemProgram
   >,
   config?: { programAddress?: TProgramAddress }
 ): InitializeTrackInstruction<
   TProgramAddress,
   TAccountAuthority,
+  TAccountArtistCounter,
   TAccountTrack,
+  TAccountProtocolConfig,
   TAccountSystemProgram
 > {
   // Program address.
@@ -272,7 +313,9 @@ export function getInitializeTrackInstruction<
   // Original accounts.
   const originalAccounts = {
     authority: { value: input.authority ?? null, isWritable: true },
+    artistCounter: { value: input.artistCounter ?? null, isWritable: true },
     track: { value: input.track ?? null, isWritable: true },
+    protocolConfig: { value: input.protocolConfig ?? null, isWritable: false },
     systemProgram: { value: input.systemProgram ?? null, isWritable: false },
   };
   const accounts = originalAccounts as Record<
@@ -293,7 +336,9 @@ export function getInitializeTrackInstruction<
   return Object.freeze({
     accounts: [
       getAccountMeta(accounts.authority),
+      getAccountMeta(accounts.artistCounter),
       getAccountMeta(accounts.track),
+      getAccountMeta(accounts.protocolConfig),
       getAccountMeta(accounts.systemProgram),
     ],
     data: getInitializeTrackInstructionDataEncoder().encode(
@@ -303,7 +348,9 @@ export function getInitializeTrackInstruction<
   } as InitializeTrackInstruction<
     TProgramAddress,
     TAccountAuthority,
+    TAccountArtistCounter,
     TAccountTrack,
+    TAccountProtocolConfig,
     TAccountSystemProgram
   >);
 }
@@ -315,8 +362,10 @@ export type ParsedInitializeTrackInstruction<
   programAddress: Address<TProgram>;
   accounts: {
     authority: TAccountMetas[0];
-    track: TAccountMetas[1];
-    systemProgram: TAccountMetas[2];
+    artistCounter: TAccountMetas[1];
+    track: TAccountMetas[2];
+    protocolConfig: TAccountMetas[3];
+    systemProgram: TAccountMetas[4];
   };
   data: InitializeTrackInstructionData;
 };
@@ -329,7 +378,7 @@ export function parseInitializeTrackInstruction<
     InstructionWithAccounts<TAccountMetas> &
     InstructionWithData<ReadonlyUint8Array>
 ): ParsedInitializeTrackInstruction<TProgram, TAccountMetas> {
-  if (instruction.accounts.length < 3) {
+  if (instruction.accounts.length < 5) {
     // TODO: Coded error.
     throw new Error('Not enough accounts');
   }
@@ -343,7 +392,9 @@ export function parseInitializeTrackInstruction<
     programAddress: instruction.programAddress,
     accounts: {
       authority: getNextAccount(),
+      artistCounter: getNextAccount(),
       track: getNextAccount(),
+      protocolConfig: getNextAccount(),
       systemProgram: getNextAccount(),
     },
     data: getInitializeTrackInstructionDataDecoder().decode(instruction.data),
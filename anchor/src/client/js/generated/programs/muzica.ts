@@ -27,6 +27,7 @@ export const MUZICA_PROGRAM_ADDRESS =
   '9NVaiC6n62KnMtVYUCcfdDY1KdAFNyZmnopdhTcvHnwJ' as Address<'9NVaiC6n62KnMtVYUCcfdDY1KdAFNyZmnopdhTcvHnwJ'>;
 
 export enum MuzicaAccount {
+  ArtistCounter,
   Track,
 }
 
@@ -34,6 +35,17 @@ export function identifyMuzicaAccount(
   account: { data: ReadonlyUint8Array } | ReadonlyUint8Array
 ): MuzicaAccount {
   const data = 'data' in account ? account.data : account;
+  if (
+    containsBytes(
+      data,
+      fixEncoderSize(getBytesEncoder(), 8).encode(
+        new Uint8Array([193, 17, 189, 197, 33, 126, 179, 204])
+      ),
+      0
+    )
+  ) {
+    return MuzicaAccount.ArtistCounter;
+  }
   if (
     containsBytes(
       data,
@@ -2,7 +2,27 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import {Muzica}  from "../target/types/muzica";
 import { describe, it, expect, vi, beforeAll } from "vitest";
-import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, getAssociatedTokenAddress, createAccount, mintTo } from "@solana/spl-token";
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  createMint,
+  getAssociatedTokenAddress,
+  getOrCreateAssociatedTokenAccount,
+  createAccount,
+  mintTo,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  setAuthority,
+  AuthorityType,
+  getMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  transfer,
+} from "@solana/spl-token";
+import legacyTrackAuthority from "./fixtures/legacy-track-authority.json";
 
 vi.setConfig({ testTimeout: 600000 });
 
@@ -20,9 +40,56 @@ describe("muzica", () => {
   let escrowAta: anchor.web3.PublicKey;
   let payerTokenAccount: anchor.web3.PublicKey;
 
+  const TOKEN_METADATA_PROGRAM_ID = new anchor.web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzJb6a8bt518x1s");
+
+  const [protocolConfigPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("protocol_config")],
+    program.programId
+  );
+
+  const earningsPda = (contributor: anchor.web3.PublicKey, track = trackPda) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("earnings"), track.toBuffer(), contributor.toBuffer()],
+      program.programId
+    )[0];
+
+  const [artistCounterPda] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("artist_counter"), wallet.publicKey.toBuffer()],
+    program.programId
+  );
+
+  // Creates another track for the wallet under the next id from its counter
+  const createTrack = async (title: string, contributors: anchor.web3.PublicKey[], shares: number[]) => {
+    const id = (await program.account.artistCounter.fetch(artistCounterPda)).trackCount;
+    const [pda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("track"), wallet.publicKey.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .initializeTrack(title, `${title}-cid`, Array(32).fill(0), contributors, shares)
+      .accountsPartial({ authority: wallet.publicKey, track: pda })
+      .rpc();
+    return { id, pda };
+  };
+
+  const fundEscrow = async (amount: number) => {
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, amount);
+    await program.methods
+      .escrowDeposit(new anchor.BN(amount), trackId, wallet.publicKey, null, null)
+      .accounts({
+        payer: wallet.publicKey,
+        escrowTokenAccount: escrowAta,
+        payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  };
+
   beforeAll(async () => {
 
-    trackId = new anchor.BN(1);
+    // first track handed out by the authority's counter
+    trackId = new anchor.BN(0);
 
   
     [trackPda] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -69,21 +136,41 @@ describe("muzica", () => {
   })
 
 
+  it("initialize protocol config", async () => {
+
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+
+    await program.methods
+      .initializeProtocolConfig(0, 1000)
+      .accounts({
+        admin: wallet.publicKey,
+        programData,
+      })
+      .rpc();
+
+    const config = await program.account.protocolConfig.fetch(protocolConfigPda);
+    expect(config.admin.toBase58()).to.equal(wallet.publicKey.toBase58());
+    expect(config.feeBps).to.equal(0);
+    expect(config.maxFeeBps).to.equal(1000);
+  });
+
   it("initialize track", async () => {
 
     
     const initializeTrackIx = await program.methods
       .initializeTrack(
-        trackId,
         "My First Track",
         "An awesome track", 
         Array(32).fill(0),
         [wallet.publicKey],
         [10000]
       )
-      .accounts({
+      .accountsPartial({
         authority: wallet.publicKey,
-       
+        track: trackPda,
       })
       .instruction();
 
@@ -96,18 +183,22 @@ describe("muzica", () => {
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     }).add(initializeTrackIx);
 
-    const signedTx = await anchor.web3.sendAndConfirmTransaction(
+    await anchor.web3.sendAndConfirmTransaction(
       provider.connection,
       tx,
       [wallet.payer]
     );
     
-    console.log("Transaction signature", signedTx);
-
     const trackAccount = await program.account.track.fetch(trackPda);
-    console.log("Track Account:", trackAccount);
     
-    expect(trackAccount.trackId.toNumber()).to.equal(1);
+    expect(trackAccount.trackId.toNumber()).to.equal(0);
+
+    const [artistCounterPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("artist_counter"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const counter = await program.account.artistCounter.fetch(artistCounterPda);
+    expect(counter.trackCount.toNumber()).to.equal(1);
     expect(trackAccount.title).to.equal("My First Track");
     expect(trackAccount.cid).to.equal("An awesome track");
     expect(trackAccount.contributors.length).to.equal(1);
@@ -115,18 +206,95 @@ describe("muzica", () => {
     expect(trackAccount.shares[0]).to.equal(10000);
   });
 
+  it("seeded artist counter skips ids held by legacy tracks", async () => {
+    const legacy = anchor.web3.Keypair.generate();
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: legacy.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL,
+        })
+      ),
+      [wallet.payer]
+    );
+
+    // ids 0..3 belong to tracks created before the counter
+    await program.methods
+      .initArtistCounter(new anchor.BN(3))
+      .accounts({ authority: legacy.publicKey })
+      .signers([legacy])
+      .rpc();
+
+    const [legacyTrackPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("track"), legacy.publicKey.toBuffer(), new anchor.BN(3).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .initializeTrack("Back Catalog", "bafylegacy", Array(32).fill(0), [legacy.publicKey], [10000])
+      .accountsPartial({ authority: legacy.publicKey, track: legacyTrackPda })
+      .signers([legacy])
+      .rpc();
+
+    const legacyTrack = await program.account.track.fetch(legacyTrackPda);
+    expect(legacyTrack.trackId.toNumber()).to.equal(3);
+
+    // the counter can only be seeded once
+    await expect(
+      program.methods
+        .initArtistCounter(new anchor.BN(0))
+        .accounts({ authority: legacy.publicKey })
+        .signers([legacy])
+        .rpc()
+    ).rejects.toThrow(/already in use/);
+  });
+
   it("stem mint", async () => {
 
-    const stemMintPubkey = anchor.web3.Keypair.generate().publicKey;
+    // a 1-of-1 handed to the track PDA; linking revokes the mint authority for good
+    const stemMintPubkey = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 0);
+    const stemHolder = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, stemMintPubkey, wallet.publicKey);
+    await mintTo(provider.connection, wallet.payer, stemMintPubkey, stemHolder.address, wallet.payer, 1);
+    await setAuthority(provider.connection, wallet.payer, stemMintPubkey, wallet.payer, AuthorityType.MintTokens, trackPda);
+    const sizeBefore = (await provider.connection.getAccountInfo(trackPda)).data.length;
+
+    await program.methods
+      .addStem(trackId, { drums: {} }, "bafystemdrums", Array(32).fill(1), wallet.publicKey)
+      .accounts({ authority: wallet.publicKey })
+      .rpc();
+
+    const foreignMint = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 0);
+    const foreignHolder = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, foreignMint, wallet.publicKey);
+    await mintTo(provider.connection, wallet.payer, foreignMint, foreignHolder.address, wallet.payer, 1);
+    await expect(
+      program.methods
+        .stemMint(trackId, 0)
+        .accounts({ authority: wallet.publicKey, mint: foreignMint, tokenProgram: TOKEN_PROGRAM_ID })
+        .rpc()
+    ).rejects.toThrow(/InvalidStemMint/);
+
+    // a 1-of-1 revoked outside the program carries no provenance
+    const revokedMint = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 0);
+    const revokedHolder = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, revokedMint, wallet.publicKey);
+    await mintTo(provider.connection, wallet.payer, revokedMint, revokedHolder.address, wallet.payer, 1);
+    await setAuthority(provider.connection, wallet.payer, revokedMint, wallet.payer, AuthorityType.MintTokens, null);
+    await expect(
+      program.methods
+        .stemMint(trackId, 0)
+        .accounts({ authority: wallet.publicKey, mint: revokedMint, tokenProgram: TOKEN_PROGRAM_ID })
+        .rpc()
+    ).rejects.toThrow(/InvalidStemMint/);
 
     const stemMintIx = await program.methods
       .stemMint(
-        trackId,           
-        stemMintPubkey     
+        trackId,
+        0
       )
       .accounts({
         authority: wallet.publicKey,
-        
+        mint: stemMintPubkey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .instruction();
 
@@ -138,19 +306,94 @@ describe("muzica", () => {
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     }).add(stemMintIx);
 
-    const signedTx = await anchor.web3.sendAndConfirmTransaction(
+    await anchor.web3.sendAndConfirmTransaction(
       provider.connection,
       tx,
       [wallet.payer]
     );
 
-    console.log("Transaction signature", signedTx);
-
     const trackAccount = await program.account.track.fetch(trackPda);
-    console.log("Track Account:", trackAccount);
 
-    expect(trackAccount.stemMints.length).to.equal(1);
-    expect(trackAccount.stemMints[0].toBase58()).to.equal(stemMintPubkey.toBase58());
+    expect(trackAccount.stemCount).to.equal(1);
+
+    const [stemPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem"), trackPda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const stem = await program.account.stem.fetch(stemPda);
+    expect(stem.stemType).to.have.property("drums");
+    expect(stem.mint.toBase58()).to.equal(stemMintPubkey.toBase58());
+
+    const linkedMint = await getMint(provider.connection, stemMintPubkey);
+    expect(linkedMint.supply).to.equal(BigInt(1));
+    expect(linkedMint.mintAuthority).to.equal(null);
+
+    // stems live in their own accounts, so the track does not grow
+    const sizeAfter = (await provider.connection.getAccountInfo(trackPda)).data.length;
+    expect(sizeAfter).to.equal(sizeBefore);
+
+    await program.methods
+      .retireStem(trackId, 0)
+      .accounts({ authority: wallet.publicKey })
+      .rpc();
+    expect((await program.account.stem.fetch(stemPda)).retired).to.equal(true);
+  });
+
+  it("resize track keeps a right-sized track unchanged", async () => {
+
+    const sizeBefore = (await provider.connection.getAccountInfo(trackPda)).data.length;
+
+    await program.methods
+      .resizeTrack(trackId)
+      .accounts({ authority: wallet.publicKey })
+      .rpc();
+
+    const sizeAfter = (await provider.connection.getAccountInfo(trackPda)).data.length;
+    expect(sizeAfter).to.equal(sizeBefore);
+    expect(sizeAfter).to.be.lessThan(1024);
+  });
+
+  it("resize track shrinks a legacy fixed-size track and refunds its rent", async () => {
+
+    // preloaded from tests/fixtures/legacy-track.json at the old INIT_SPACE
+    const legacyAuthority = anchor.web3.Keypair.fromSecretKey(Uint8Array.from(legacyTrackAuthority));
+    const legacyId = new anchor.BN(0);
+    const [legacyTrack] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("track"), legacyAuthority.publicKey.toBuffer(), legacyId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const before = await provider.connection.getAccountInfo(legacyTrack);
+    expect(before.data.length).to.equal(82273);
+
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: legacyAuthority.publicKey, lamports: 10_000_000 })
+      ),
+      [wallet.payer]
+    );
+    const authorityBefore = await provider.connection.getBalance(legacyAuthority.publicKey);
+
+    // the provider wallet pays the fee, so the authority's balance only moves by the refund
+    await program.methods
+      .resizeTrack(legacyId)
+      .accounts({ authority: legacyAuthority.publicKey })
+      .signers([legacyAuthority])
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(legacyTrack);
+    const rightSized = 8 + 32 + 8 + (4 + 6) + (4 + 10) + 32 + (4 + 32) + (4 + 2) + 4 + 4 + 1 + 1 + (1 + 32) + 4 + (1 + 32) + 4 + 2;
+    expect(after.data.length).to.equal(rightSized);
+    expect(after.lamports).to.equal(await provider.connection.getMinimumBalanceForRentExemption(rightSized));
+
+    const authorityAfter = await provider.connection.getBalance(legacyAuthority.publicKey);
+    expect(authorityAfter - authorityBefore).to.equal(before.lamports - after.lamports);
+
+    const legacy = await program.account.track.fetch(legacyTrack);
+    expect(legacy.title).to.equal("Legacy");
+    expect(legacy.shares).to.deep.equal([10000]);
+    expect(legacy.royaltyStems.length).to.equal(0);
   });
 
   it("update shares", async () => {
@@ -177,16 +420,13 @@ describe("muzica", () => {
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     }).add(updateSharesIx);
 
-    const signedTx = await anchor.web3.sendAndConfirmTransaction(
+    await anchor.web3.sendAndConfirmTransaction(
       provider.connection,
       tx,
       [wallet.payer]
     );
 
-    console.log("Transaction signature", signedTx);
-
     const trackAccount = await program.account.track.fetch(trackPda);
-    console.log("Track Account:", trackAccount);
 
     expect(trackAccount.shares.length).to.equal(3);
     expect(trackAccount.shares[0]).to.equal(6000);
@@ -204,6 +444,7 @@ it ("create escrow", async () => {
       payer: wallet.publicKey,
       escrowTokenAccount: escrowAta,
       mint: mintPublicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
     })
     .instruction();
 
@@ -215,20 +456,84 @@ it ("create escrow", async () => {
     lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
   }).add(createEscrowIx);
 
-  const signedTx = await anchor.web3.sendAndConfirmTransaction(
+  await anchor.web3.sendAndConfirmTransaction(
     provider.connection,
     tx,
     [wallet.payer]
   );
 
-  console.log("Transaction signature", signedTx);
-
   let escrowAccountInfo = await provider.connection.getAccountInfo(escrowAta);
-  console.log("Escrow Account Info:", escrowAccountInfo);
 
   expect(escrowAccountInfo).to.not.be.null;
   });
 
+  it ("add accepted mint", async () => {
+
+    const addMintIx = await program.methods
+      .addAcceptedMint(trackId)
+      .accounts({
+        authority: wallet.publicKey,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+
+    let blockhashContext = await provider.connection.getLatestBlockhash();
+
+    const tx = new anchor.web3.Transaction({
+      feePayer: wallet.publicKey,
+      blockhash: blockhashContext.blockhash,
+      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
+    }).add(addMintIx);
+
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      tx,
+      [wallet.payer]
+    );
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+
+    const registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    expect(registry.mints.length).to.equal(1);
+    expect(registry.mints[0].mint.toBase58()).to.equal(mintPublicKey.toBase58());
+    expect(registry.mints[0].escrow.toBase58()).to.equal(escrowAta.toBase58());
+  });
+
+  it ("rejects deposit in unlisted mint", async () => {
+
+    const otherMint = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 6);
+    const otherEscrow = await getAssociatedTokenAddress(otherMint, trackPda, true);
+    const otherPayerAccount = await createAccount(provider.connection, wallet.payer, otherMint, wallet.publicKey);
+    await mintTo(provider.connection, wallet.payer, otherMint, otherPayerAccount, wallet.payer, 1_000);
+
+    await program.methods
+      .createEscrowAta(trackId, wallet.publicKey)
+      .accounts({
+        payer: wallet.publicKey,
+        escrowTokenAccount: otherEscrow,
+        mint: otherMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await expect(
+      program.methods
+        .escrowDeposit(new anchor.BN(1_000), trackId, wallet.publicKey, null, null)
+        .accounts({
+          payer: wallet.publicKey,
+          escrowTokenAccount: otherEscrow,
+          payerTokenAccount: otherPayerAccount,
+          mint: otherMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc()
+    ).rejects.toThrow(/MintNotAccepted/);
+  });
+
   it ("deposit to escrow", async () => {
 
     
@@ -240,11 +545,15 @@ it ("create escrow", async () => {
         new anchor.BN(depositAmount),
         trackId,               
         wallet.publicKey,    
+        null,
+        null,
       )
       .accounts({
         payer: wallet.publicKey,
         escrowTokenAccount: escrowAta,
         payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .instruction();
 
@@ -256,16 +565,11 @@ it ("create escrow", async () => {
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     }).add(depositEscrowIx);
 
-    const signedTx = await anchor.web3.sendAndConfirmTransaction(
+    await anchor.web3.sendAndConfirmTransaction(
       provider.connection,
       tx,
       [wallet.payer]
     );
-
-    console.log("Transaction signature", signedTx);
-
-    console.log("Deposited", depositAmount, "tokens to escrow account");
-  
   });
 
   it ("distribute from escrow", async () => {
@@ -284,12 +588,20 @@ it ("create escrow", async () => {
       )
       .accounts({
         escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
       })
       .remainingAccounts([
         {
           pubkey: contributorAta,
           isWritable: true,
           isSigner: false,
+        },
+        {
+          pubkey: earningsPda(wallet.publicKey),
+          isWritable: true,
+          isSigner: false,
         }
       ])
       .instruction();
@@ -302,30 +614,1074 @@ it ("create escrow", async () => {
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     }).add(distributeIx);
 
-    const signedTx = await anchor.web3.sendAndConfirmTransaction(
+    await anchor.web3.sendAndConfirmTransaction(
       provider.connection,
       tx,
       [wallet.payer]
     );
+  });
 
-    console.log("Transaction signature", signedTx);
+  it ("distribute all registered mints", async () => {
 
-    console.log("Distributed tokens from escrow to contributors");
-  
+    const contributorAta = await getAssociatedTokenAddress(
+      mintPublicKey,
+      wallet.publicKey,
+      false
+    );
+
+    await program.methods
+      .escrowDistributeAll(trackId)
+      .accounts({
+        authority: wallet.publicKey,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: escrowAta, isWritable: true, isSigner: false },
+        { pubkey: mintPublicKey, isWritable: false, isSigner: false },
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const escrowBalance = await provider.connection.getTokenAccountBalance(escrowAta);
+    expect(Number(escrowBalance.value.amount)).to.be.lessThan(1_000_000);
   });
 
-  it ("mint stem NFT", async () => {
+  it ("skims protocol fee on distribute", async () => {
 
-    const stemNftMintIx = await program.methods
-      .mintStemNft(
-        trackId, 
-        new anchor.BN(0)            
-      )
+    const feeWallet = anchor.web3.Keypair.generate();
+    const feeAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      wallet.payer,
+      mintPublicKey,
+      feeWallet.publicKey
+    );
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+    await fundEscrow(200_000);
+
+    await expect(
+      program.methods.setProtocolFee(2000).accounts({ admin: wallet.publicKey }).rpc()
+    ).rejects.toThrow(/FeeAboveMax/);
+
+    await program.methods.setProtocolFee(500).accounts({ admin: wallet.publicKey }).rpc();
+    await program.methods
+      .setFeeRecipient(mintPublicKey, feeWallet.publicKey)
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .escrowDistribute(new anchor.BN(100_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        { pubkey: feeAta.address, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    let feeBalance = await provider.connection.getTokenAccountBalance(feeAta.address);
+    expect(Number(feeBalance.value.amount)).to.equal(5_000);
+
+    // partner tracks skip the fee entirely
+    await program.methods
+      .addFeeExemption()
+      .accounts({ admin: wallet.publicKey, track: trackPda })
+      .rpc();
+
+    const [feeExemptionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_exemption"), trackPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .escrowDistribute(new anchor.BN(100_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: feeExemptionPda,
+      })
+      .remainingAccounts([
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    feeBalance = await provider.connection.getTokenAccountBalance(feeAta.address);
+    expect(Number(feeBalance.value.amount)).to.equal(5_000);
+
+    await program.methods
+      .removeFeeExemption()
+      .accounts({ admin: wallet.publicKey, feeExemption: feeExemptionPda })
+      .rpc();
+    await program.methods.setProtocolFee(0).accounts({ admin: wallet.publicKey }).rpc();
+  });
+
+  it ("global and track pause block deposits", async () => {
+
+    const deposit = () =>
+      program.methods
+        .escrowDeposit(new anchor.BN(1), trackId, wallet.publicKey, null, null)
+        .accounts({
+          payer: wallet.publicKey,
+          escrowTokenAccount: escrowAta,
+          payerTokenAccount: payerTokenAccount,
+          mint: mintPublicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const resize = () =>
+      program.methods.resizeTrack(trackId).accounts({ authority: wallet.publicKey }).rpc();
+
+    const newcomer = anchor.web3.Keypair.generate();
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: newcomer.publicKey, lamports: 10_000_000 })
+      ),
+      [wallet.payer]
+    );
+
+    await program.methods.setGlobalPause(true).accounts({ guardian: wallet.publicKey }).rpc();
+    await expect(deposit()).rejects.toThrow(/ProtocolPaused/);
+    await expect(resize()).rejects.toThrow(/ProtocolPaused/);
+    await expect(
+      program.methods
+        .initArtistCounter(new anchor.BN(0))
+        .accounts({ authority: newcomer.publicKey })
+        .signers([newcomer])
+        .rpc()
+    ).rejects.toThrow(/ProtocolPaused/);
+    await program.methods.setGlobalPause(false).accounts({ guardian: wallet.publicKey }).rpc();
+
+    await program.methods.setTrackPause(trackId, true).accounts({ authority: wallet.publicKey }).rpc();
+    await expect(deposit()).rejects.toThrow(/TrackPaused/);
+    await expect(resize()).rejects.toThrow(/TrackPaused/);
+    await program.methods.setTrackPause(trackId, false).accounts({ authority: wallet.publicKey }).rpc();
+
+    const trackAccount = await program.account.track.fetch(trackPda);
+    expect(trackAccount.paused).to.equal(false);
+  });
+
+  it ("held deposit can be refunded during its window", async () => {
+
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, 50_000);
+    const payerBalanceBefore = await provider.connection.getTokenAccountBalance(payerTokenAccount);
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const registryBefore = await program.account.mintRegistry.fetch(mintRegistryPda);
+    const depositId = registryBefore.depositCount;
+
+    await program.methods
+      .escrowDepositHeld(new anchor.BN(50_000), trackId, wallet.publicKey, new anchor.BN(3600), null, null)
       .accounts({
         payer: wallet.publicKey,
-        authority: wallet.publicKey,
+        escrowTokenAccount: escrowAta,
+        payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
-      .instruction();
+      .rpc();
+
+    const [depositPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), trackPda.toBuffer(), depositId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    let registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    expect(registry.mints[0].held.toNumber()).to.equal(50_000);
+
+    let deposit = await program.account.deposit.fetch(depositPda);
+    expect(deposit.amount.toNumber()).to.equal(50_000);
+    expect(deposit.status).to.have.property("held");
+
+    await expect(
+      program.methods
+        .releaseDeposit(trackId, depositId)
+        .accounts({ track: trackPda })
+        .rpc()
+    ).rejects.toThrow(/HoldPeriodActive/);
+
+    // a pause must not eat into the refund window
+    await program.methods.setGlobalPause(true).accounts({ guardian: wallet.publicKey }).rpc();
+    await program.methods.setTrackPause(trackId, true).accounts({ authority: wallet.publicKey }).rpc();
+
+    await program.methods
+      .refundDeposit(trackId, depositId)
+      .accounts({
+        caller: wallet.publicKey,
+        track: trackPda,
+        depositorTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods.setTrackPause(trackId, false).accounts({ authority: wallet.publicKey }).rpc();
+    await program.methods.setGlobalPause(false).accounts({ guardian: wallet.publicKey }).rpc();
+
+    registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    expect(registry.mints[0].held.toNumber()).to.equal(0);
+
+    deposit = await program.account.deposit.fetch(depositPda);
+    expect(deposit.status).to.have.property("refunded");
+
+    const payerBalance = await provider.connection.getTokenAccountBalance(payerTokenAccount);
+    expect(payerBalance.value.amount).to.equal(payerBalanceBefore.value.amount);
+  });
+
+  it ("records deposit reference and memo in the ledger", async () => {
+
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, 10_000);
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const depositId = (await program.account.mintRegistry.fetch(mintRegistryPda)).depositCount;
+    const reference = anchor.web3.Keypair.generate().publicKey;
+
+    await program.methods
+      .escrowDepositHeld(new anchor.BN(10_000), trackId, wallet.publicKey, new anchor.BN(0), reference, "INV-2024-0042")
+      .accounts({
+        payer: wallet.publicKey,
+        escrowTokenAccount: escrowAta,
+        payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: reference, isWritable: false, isSigner: false },
+      ])
+      .rpc();
+
+    const [depositPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), trackPda.toBuffer(), depositId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const deposit = await program.account.deposit.fetch(depositPda);
+    expect(deposit.reference.toBase58()).to.equal(reference.toBase58());
+    expect(deposit.memo).to.equal("INV-2024-0042");
+    expect(deposit.status).to.have.property("released");
+
+    // the reference key is on the transaction so payments can be found by it
+    const signatures = await provider.connection.getSignaturesForAddress(reference);
+    expect(signatures.length).to.equal(1);
+  });
+
+  it ("records plain escrow deposits with a reference in the ledger", async () => {
+
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, 5_000);
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const depositId = (await program.account.mintRegistry.fetch(mintRegistryPda)).depositCount;
+    const [depositPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), trackPda.toBuffer(), depositId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const reference = anchor.web3.Keypair.generate().publicKey;
+
+    const deposit = (ledgerEntry: anchor.web3.PublicKey | null) =>
+      program.methods
+        .escrowDeposit(new anchor.BN(5_000), trackId, wallet.publicKey, reference, "INV-2024-0043")
+        .accounts({
+          payer: wallet.publicKey,
+          escrowTokenAccount: escrowAta,
+          payerTokenAccount: payerTokenAccount,
+          mint: mintPublicKey,
+          deposit: ledgerEntry,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await expect(deposit(null)).rejects.toThrow(/MissingDepositRecord/);
+    await deposit(depositPda);
+
+    const record = await program.account.deposit.fetch(depositPda);
+    expect(record.reference.toBase58()).to.equal(reference.toBase58());
+    expect(record.memo).to.equal("INV-2024-0043");
+    expect(record.amount.toNumber()).to.equal(5_000);
+    expect(record.status).to.have.property("released");
+  });
+
+  it ("tracks lifetime totals per track and contributor", async () => {
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+
+    // with a 6000/3000/1000 split, 7 leaves 1 unit of dust that the next settlement sweeps
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+    const distribute = (amount: number) =>
+      program.methods
+        .escrowDistribute(new anchor.BN(amount), trackId)
+        .accounts({
+          escrowTokenAccount: escrowAta,
+          mint: mintPublicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          feeExemption: null,
+        })
+        .remainingAccounts([
+          { pubkey: contributorAta, isWritable: true, isSigner: false },
+          { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        ])
+        .rpc();
+    const dust = async () => (await program.account.mintRegistry.fetch(mintRegistryPda)).mints[0].dustOutstanding.toNumber();
+
+    await fundEscrow(1_017);
+    await distribute(1_000);
+    expect(await dust()).to.equal(0);
+    await distribute(7);
+    expect(await dust()).to.equal(1);
+    await distribute(10);
+    expect(await dust()).to.equal(0);
+
+    const registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    const entry = registry.mints[0];
+    const escrowBalance = await provider.connection.getTokenAccountBalance(escrowAta);
+
+    // everything deposited is either paid out, taken as fee or still sitting in the escrow
+    expect(
+      entry.totalDistributed.add(entry.totalProtocolFees).add(new anchor.BN(escrowBalance.value.amount)).toString()
+    ).to.equal(entry.totalDeposited.toString());
+
+    const earnings = await program.account.contributorEarnings.fetch(earningsPda(wallet.publicKey));
+    const paid = earnings.totals.find((t) => t.mint.equals(mintPublicKey));
+    expect(paid.paid.toString()).to.equal(entry.totalDistributed.toString());
+  });
+
+  it ("contributor profile aggregates tracks and earnings", async () => {
+
+    const [profilePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createProfile("Producer One", null)
+      .accounts({ wallet: wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .updateShares(trackId, [10000], [wallet.publicKey])
+      .accounts({ authority: wallet.publicKey })
+      .remainingAccounts([
+        { pubkey: profilePda, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    let profile = await program.account.contributorProfile.fetch(profilePda);
+    expect(profile.displayName).to.equal("Producer One");
+    expect(profile.tracks.map((t) => t.toBase58())).to.include(trackPda.toBase58());
+
+    await fundEscrow(40_000);
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+
+    await program.methods
+      .escrowDistribute(new anchor.BN(40_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        { pubkey: profilePda, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    profile = await program.account.contributorProfile.fetch(profilePda);
+    const earned = profile.earnings.find((e) => e.mint.equals(mintPublicKey));
+    expect(earned.amount.toNumber()).to.equal(40_000);
+
+    // removing a contributor needs their profile so the track is dropped from it
+    const standIn = anchor.web3.Keypair.generate().publicKey;
+    const [standInProfile] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), standIn.toBuffer()],
+      program.programId
+    );
+    await expect(
+      program.methods
+        .updateShares(trackId, [10000], [standIn])
+        .accounts({ authority: wallet.publicKey })
+        .rpc()
+    ).rejects.toThrow(/MissingProfileAccount/);
+
+    await program.methods
+      .updateShares(trackId, [10000], [standIn])
+      .accounts({ authority: wallet.publicKey })
+      .remainingAccounts([{ pubkey: profilePda, isWritable: true, isSigner: false }])
+      .rpc();
+    profile = await program.account.contributorProfile.fetch(profilePda);
+    expect(profile.tracks.map((t) => t.toBase58())).not.to.include(trackPda.toBase58());
+
+    // the stand-in never made a profile, so passing its empty PDA is enough
+    await program.methods
+      .updateShares(trackId, [10000], [wallet.publicKey])
+      .accounts({ authority: wallet.publicKey })
+      .remainingAccounts([
+        { pubkey: profilePda, isWritable: true, isSigner: false },
+        { pubkey: standInProfile, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+    profile = await program.account.contributorProfile.fetch(profilePda);
+    expect(profile.tracks.map((t) => t.toBase58())).to.include(trackPda.toBase58());
+  });
+
+  it ("claims credited balances from many tracks in one call", async () => {
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const [profilePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+
+    await fundEscrow(30_000);
+
+    await program.methods
+      .escrowCredit(new anchor.BN(30_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    let earnings = await program.account.contributorEarnings.fetch(earningsPda(wallet.publicKey));
+    expect(earnings.totals.find((t) => t.mint.equals(mintPublicKey)).claimable.toNumber()).to.equal(30_000);
+
+    const balanceBefore = await provider.connection.getTokenAccountBalance(contributorAta);
+
+    await program.methods
+      .claimEarnings()
+      .accounts({
+        contributor: wallet.publicKey,
+        contributorTokenAccount: contributorAta,
+        mint: mintPublicKey,
+        profile: profilePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: trackPda, isWritable: false, isSigner: false },
+        { pubkey: mintRegistryPda, isWritable: true, isSigner: false },
+        { pubkey: escrowAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const balanceAfter = await provider.connection.getTokenAccountBalance(contributorAta);
+    expect(Number(balanceAfter.value.amount) - Number(balanceBefore.value.amount)).to.equal(30_000);
+
+    earnings = await program.account.contributorEarnings.fetch(earningsPda(wallet.publicKey));
+    expect(earnings.totals.find((t) => t.mint.equals(mintPublicKey)).claimable.toNumber()).to.equal(0);
+
+    const registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    expect(registry.mints[0].creditedOutstanding.toNumber()).to.equal(0);
+  });
+
+  it ("settles a catalog through a resumable distribution run", async () => {
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const [runPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("distribution_run"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+
+    await fundEscrow(25_000);
+
+    await program.methods
+      .startDistributionRun([trackPda])
+      .accounts({ authority: wallet.publicKey, mint: mintPublicKey })
+      .rpc();
+
+    const continueRun = () =>
+      program.methods
+        .continueDistributionRun()
+        .accounts({
+          authority: wallet.publicKey,
+          mint: mintPublicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: trackPda, isWritable: false, isSigner: false },
+          { pubkey: mintRegistryPda, isWritable: true, isSigner: false },
+          { pubkey: escrowAta, isWritable: true, isSigner: false },
+          { pubkey: contributorAta, isWritable: true, isSigner: false },
+          { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        ])
+        .rpc();
+
+    await continueRun();
+
+    const run = await program.account.distributionRun.fetch(runPda);
+    expect(run.cursor).to.equal(1);
+
+    const registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    const escrowBalance = await provider.connection.getTokenAccountBalance(escrowAta);
+    expect(Number(escrowBalance.value.amount)).to.equal(
+      registry.mints[0].held.add(registry.mints[0].creditedOutstanding).toNumber()
+    );
+
+    await expect(continueRun()).rejects.toThrow(/DistributionRunComplete/);
+  });
+
+  it ("distribution run stops on missing accounts and defers paused tracks", async () => {
+
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const [runPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("distribution_run"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+
+    // a track that never set up a mint registry
+    const bare = await createTrack("No Registry", [wallet.publicKey], [10000]);
+    const [bareRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), bare.pda.toBuffer()],
+      program.programId
+    );
+
+    await fundEscrow(10_000);
+
+    await program.methods
+      .startDistributionRun([bare.pda, trackPda])
+      .accounts({ authority: wallet.publicKey, mint: mintPublicKey })
+      .rpc();
+
+    const continueRun = (accounts: anchor.web3.PublicKey[]) =>
+      program.methods
+        .continueDistributionRun()
+        .accounts({
+          authority: wallet.publicKey,
+          mint: mintPublicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(accounts.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .rpc();
+
+    const trackAccounts = [
+      trackPda,
+      mintRegistryPda,
+      escrowAta,
+      contributorAta,
+      earningsPda(wallet.publicKey),
+    ];
+
+    // without its registry the run cannot tell whether the track accepts the mint
+    await expect(continueRun([bare.pda])).rejects.toThrow(/DistributionRunStalled/);
+    let run = await program.account.distributionRun.fetch(runPda);
+    expect(run.cursor).to.equal(0);
+
+    // the empty registry PDA proves the track takes no mints; the paused track waits
+    await program.methods.setTrackPause(trackId, true).accounts({ authority: wallet.publicKey }).rpc();
+    await continueRun([bare.pda, bareRegistryPda, ...trackAccounts]);
+    run = await program.account.distributionRun.fetch(runPda);
+    expect(run.cursor).to.equal(1);
+
+    await expect(continueRun(trackAccounts)).rejects.toThrow(/DistributionRunStalled/);
+
+    await program.methods.setTrackPause(trackId, false).accounts({ authority: wallet.publicKey }).rpc();
+    await continueRun(trackAccounts);
+    run = await program.account.distributionRun.fetch(runPda);
+    expect(run.cursor).to.equal(2);
+
+    const registry = await program.account.mintRegistry.fetch(mintRegistryPda);
+    const escrowBalance = await provider.connection.getTokenAccountBalance(escrowAta);
+    expect(Number(escrowBalance.value.amount)).to.equal(
+      registry.mints[0].held.add(registry.mints[0].creditedOutstanding).toNumber()
+    );
+  });
+
+  it ("catalog takes a label cut before the track split and moves with its owner", async () => {
+
+    const catalogId = new anchor.BN(1);
+    const [catalogPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("catalog"), wallet.publicKey.toBuffer(), catalogId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+
+    await program.methods
+      .createCatalog(catalogId, "Night Shift Records", 1000)
+      .accounts({ owner: wallet.publicKey })
+      .rpc();
+
+    await program.methods
+      .addTrackToCatalog(trackId)
+      .accounts({ authority: wallet.publicKey, admin: wallet.publicKey, catalog: catalogPda })
+      .rpc();
+
+    const earningsBefore = await program.account.contributorEarnings.fetch(earningsPda(wallet.publicKey));
+    const paidBefore = earningsBefore.totals.find((t) => t.mint.equals(mintPublicKey)).paid;
+
+    await fundEscrow(10_000);
+
+    await program.methods
+      .escrowDistribute(new anchor.BN(10_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: catalogPda, isWritable: false, isSigner: false },
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    // 10% label cut, the rest through the track split
+    const earningsAfter = await program.account.contributorEarnings.fetch(earningsPda(wallet.publicKey));
+    const paidAfter = earningsAfter.totals.find((t) => t.mint.equals(mintPublicKey)).paid;
+    expect(paidAfter.sub(paidBefore).toNumber()).to.equal(9_000);
+
+    // raising the split does not reach tracks that joined at the old one
+    await program.methods
+      .setCatalogSplit(10000)
+      .accounts({ owner: wallet.publicKey, catalog: catalogPda })
+      .rpc();
+
+    await fundEscrow(10_000);
+
+    await program.methods
+      .escrowDistribute(new anchor.BN(10_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: catalogPda, isWritable: false, isSigner: false },
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const earningsCapped = await program.account.contributorEarnings.fetch(earningsPda(wallet.publicKey));
+    const paidCapped = earningsCapped.totals.find((t) => t.mint.equals(mintPublicKey)).paid;
+    expect(paidCapped.sub(paidAfter).toNumber()).to.equal(9_000);
+
+    await program.methods
+      .setCatalogSplit(1000)
+      .accounts({ owner: wallet.publicKey, catalog: catalogPda })
+      .rpc();
+
+    const newOwner = anchor.web3.Keypair.generate();
+    await program.methods
+      .transferCatalog(newOwner.publicKey)
+      .accounts({ owner: wallet.publicKey, catalog: catalogPda })
+      .rpc();
+
+    let catalog = await program.account.catalog.fetch(catalogPda);
+    expect(catalog.owner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+    expect(catalog.trackCount).to.equal(1);
+
+    await program.methods
+      .removeTrackFromCatalog(trackId)
+      .accounts({ admin: newOwner.publicKey, catalog: catalogPda, track: trackPda })
+      .signers([newOwner])
+      .rpc();
+
+    let track = await program.account.track.fetch(trackPda);
+    expect(track.catalog).to.equal(null);
+
+    // the track authority can walk away on its own
+    await program.methods
+      .addTrackToCatalog(trackId)
+      .accounts({ authority: wallet.publicKey, admin: newOwner.publicKey, catalog: catalogPda })
+      .signers([newOwner])
+      .rpc();
+
+    await program.methods
+      .leaveCatalog(trackId)
+      .accounts({ authority: wallet.publicKey, catalog: catalogPda })
+      .rpc();
+
+    track = await program.account.track.fetch(trackPda);
+    expect(track.catalog).to.equal(null);
+    expect(track.catalogShareCap).to.equal(0);
+    catalog = await program.account.catalog.fetch(catalogPda);
+    expect(catalog.trackCount).to.equal(0);
+  });
+
+  it ("splits a release deposit across member track escrows", async () => {
+
+    const releaseId = new anchor.BN(1);
+    const [releasePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("release"), wallet.publicKey.toBuffer(), releaseId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+
+    const createRelease = (tracks: { track: anchor.web3.PublicKey; weightBps: number }[]) =>
+      program.methods
+        .createRelease(
+          releaseId,
+          "Late Night EP",
+          "012345678905",
+          new anchor.BN(1_700_000_000),
+          "bafybeigdyrztcover",
+          tracks
+        )
+        .accounts({ authority: wallet.publicKey })
+        .remainingAccounts(
+          tracks.map(({ track }) => ({ pubkey: track, isWritable: false, isSigner: false }))
+        )
+        .rpc();
+
+    // members must be track accounts, listed once, with weights covering the whole payment
+    await expect(createRelease([{ track: mintRegistryPda, weightBps: 10000 }])).rejects.toThrow(/InvalidReleaseTrack/);
+    await expect(
+      createRelease([{ track: trackPda, weightBps: 5000 }, { track: trackPda, weightBps: 5000 }])
+    ).rejects.toThrow(/DuplicateReleaseTrack/);
+    await expect(createRelease([{ track: trackPda, weightBps: 9000 }])).rejects.toThrow(/InvalidShareTotal/);
+
+    await createRelease([{ track: trackPda, weightBps: 10000 }]);
+
+    const release = await program.account.release.fetch(releasePda);
+    expect(release.upc).to.equal("012345678905");
+    expect(release.tracks.length).to.equal(1);
+
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, 7_000);
+    const registryBefore = await program.account.mintRegistry.fetch(mintRegistryPda);
+
+    await program.methods
+      .depositToRelease(new anchor.BN(5_000), null, null)
+      .accounts({
+        payer: wallet.publicKey,
+        release: releasePda,
+        payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: trackPda, isWritable: false, isSigner: false },
+        { pubkey: mintRegistryPda, isWritable: true, isSigner: false },
+        { pubkey: escrowAta, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const registryAfter = await program.account.mintRegistry.fetch(mintRegistryPda);
+    expect(
+      registryAfter.mints[0].totalDeposited.sub(registryBefore.mints[0].totalDeposited).toNumber()
+    ).to.equal(5_000);
+
+    // a referenced release payment leaves a ledger entry on each member track
+    const reference = anchor.web3.Keypair.generate().publicKey;
+    const [depositPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), trackPda.toBuffer(), registryAfter.depositCount.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const releaseDeposit = (remaining: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .depositToRelease(new anchor.BN(2_000), reference, "ALBUM-7")
+        .accounts({
+          payer: wallet.publicKey,
+          release: releasePda,
+          payerTokenAccount: payerTokenAccount,
+          mint: mintPublicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(remaining)
+        .rpc();
+    const memberAccounts = [
+      { pubkey: trackPda, isWritable: false, isSigner: false },
+      { pubkey: mintRegistryPda, isWritable: true, isSigner: false },
+      { pubkey: escrowAta, isWritable: true, isSigner: false },
+    ];
+
+    await expect(releaseDeposit(memberAccounts)).rejects.toThrow(/MissingDepositRecord/);
+    await releaseDeposit([...memberAccounts, { pubkey: depositPda, isWritable: true, isSigner: false }]);
+
+    const deposit = await program.account.deposit.fetch(depositPda);
+    expect(deposit.track.toBase58()).to.equal(trackPda.toBase58());
+    expect(deposit.amount.toNumber()).to.equal(2_000);
+    expect(deposit.reference.toBase58()).to.equal(reference.toBase58());
+    expect(deposit.memo).to.equal("ALBUM-7");
+    const registryReferenced = await program.account.mintRegistry.fetch(mintRegistryPda);
+    expect(registryReferenced.depositCount.toNumber()).to.equal(registryAfter.depositCount.toNumber() + 1);
+    expect(
+      registryReferenced.mints[0].totalDeposited.sub(registryAfter.mints[0].totalDeposited).toNumber()
+    ).to.equal(2_000);
+  });
+
+  it ("closes an abandoned draft track and refunds its rent", async () => {
+
+    const [artistCounterPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("artist_counter"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const draftId = (await program.account.artistCounter.fetch(artistCounterPda)).trackCount;
+    const [draftPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("track"), wallet.publicKey.toBuffer(), draftId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+      .initializeTrack("Draft", "draft-cid", Array(32).fill(0), [wallet.publicKey], [10000])
+      .accountsPartial({ authority: wallet.publicKey, track: draftPda })
+      .rpc();
+
+    const balanceBefore = await provider.connection.getBalance(wallet.publicKey);
+
+    await program.methods
+      .closeTrack(draftId)
+      .accounts({ authority: wallet.publicKey })
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(draftPda)).to.equal(null);
+    const balanceAfter = await provider.connection.getBalance(wallet.publicKey);
+    expect(balanceAfter).to.be.greaterThan(balanceBefore);
+  });
+
+  it ("refuses to close a track that still holds funds", async () => {
+
+    const draft = await createTrack("Funded Draft", [wallet.publicKey], [10000]);
+    const [draftRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), draft.pda.toBuffer()],
+      program.programId
+    );
+    const [draftVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_vault"), draft.pda.toBuffer()],
+      program.programId
+    );
+    const draftEscrow = getAssociatedTokenAddressSync(mintPublicKey, draft.pda, true);
+
+    await program.methods
+      .addAcceptedMint(draft.id)
+      .accounts({ authority: wallet.publicKey, mint: mintPublicKey, tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, 1_000);
+    await program.methods
+      .escrowDeposit(new anchor.BN(1_000), draft.id, wallet.publicKey, null, null)
+      .accounts({
+        payer: wallet.publicKey,
+        escrowTokenAccount: draftEscrow,
+        payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const closeDraft = (registry = draftRegistry) =>
+      program.methods
+        .closeTrack(draft.id)
+        .accountsPartial({ authority: wallet.publicKey, track: draft.pda, mintRegistry: registry, solVault: draftVault })
+        .remainingAccounts([{ pubkey: draftEscrow, isWritable: true, isSigner: false }])
+        .rpc();
+
+    await expect(closeDraft()).rejects.toThrow(/EscrowNotEmpty/);
+
+    // the registry cannot be left out to skip the escrow checks
+    await expect(closeDraft(wallet.publicKey)).rejects.toThrow(/ConstraintSeeds/);
+
+    await program.methods
+      .escrowDistribute(new anchor.BN(1_000), draft.id)
+      .accountsPartial({
+        track: draft.pda,
+        mintRegistry: draftRegistry,
+        escrowTokenAccount: draftEscrow,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false), isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey, draft.pda), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    // a funded SOL vault blocks the close as well
+    await program.methods.createSolVault(draft.id, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+    await program.methods.solDeposit(new anchor.BN(1_000_000), draft.id, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+    await expect(closeDraft()).rejects.toThrow(/EscrowNotEmpty/);
+
+    expect(await provider.connection.getAccountInfo(draft.pda)).to.not.equal(null);
+    expect(await provider.connection.getAccountInfo(draftRegistry)).to.not.equal(null);
+  });
+
+  it ("sweeps rounding dust to the first contributor when closing a split track", async () => {
+
+    const other = anchor.web3.Keypair.generate();
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: other.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 100,
+        })
+      ),
+      [wallet.payer]
+    );
+
+    const split = await createTrack("Split Draft", [wallet.publicKey, other.publicKey], [6667, 3333]);
+    const [splitRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), split.pda.toBuffer()],
+      program.programId
+    );
+    const [splitVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_vault"), split.pda.toBuffer()],
+      program.programId
+    );
+    const splitEscrow = getAssociatedTokenAddressSync(mintPublicKey, split.pda, true);
+    const walletAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+    const otherAta = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, mintPublicKey, other.publicKey);
+
+    await program.methods
+      .addAcceptedMint(split.id)
+      .accounts({ authority: wallet.publicKey, mint: mintPublicKey, tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
+    await mintTo(provider.connection, wallet.payer, mintPublicKey, payerTokenAccount, wallet.payer, 1_001);
+    await program.methods
+      .escrowDeposit(new anchor.BN(1_001), split.id, wallet.publicKey, null, null)
+      .accounts({
+        payer: wallet.publicKey,
+        escrowTokenAccount: splitEscrow,
+        payerTokenAccount: payerTokenAccount,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // 667 + 333 paid, one unit of dust stays in the escrow
+    await program.methods
+      .escrowDistribute(new anchor.BN(1_001), split.id)
+      .accountsPartial({
+        track: split.pda,
+        mintRegistry: splitRegistry,
+        escrowTokenAccount: splitEscrow,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: walletAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey, split.pda), isWritable: true, isSigner: false },
+        { pubkey: otherAta.address, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(other.publicKey, split.pda), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+    expect(Number((await provider.connection.getTokenAccountBalance(splitEscrow)).value.amount)).to.equal(1);
+
+    // same for SOL: 666_700 + 333_300 paid, one lamport left over
+    await program.methods.createSolVault(split.id, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+    await program.methods.solDeposit(new anchor.BN(1_000_001), split.id, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+    await program.methods
+      .solDistribute(new anchor.BN(1_000_001), split.id)
+      .accountsPartial({ authority: wallet.publicKey, track: split.pda, feeExemption: null })
+      .remainingAccounts([
+        { pubkey: wallet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey, split.pda), isWritable: true, isSigner: false },
+        { pubkey: other.publicKey, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(other.publicKey, split.pda), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const walletBalanceBefore = Number((await provider.connection.getTokenAccountBalance(walletAta)).value.amount);
+
+    await program.methods
+      .closeTrack(split.id)
+      .accountsPartial({ authority: wallet.publicKey, track: split.pda, mintRegistry: splitRegistry, solVault: splitVault })
+      .remainingAccounts([
+        { pubkey: splitEscrow, isWritable: true, isSigner: false },
+        { pubkey: mintPublicKey, isWritable: false, isSigner: false },
+        { pubkey: walletAta, isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const walletBalanceAfter = Number((await provider.connection.getTokenAccountBalance(walletAta)).value.amount);
+    expect(walletBalanceAfter - walletBalanceBefore).to.equal(1);
+    for (const closed of [split.pda, splitRegistry, splitVault, splitEscrow]) {
+      expect(await provider.connection.getAccountInfo(closed)).to.equal(null);
+    }
+  });
+
+  it ("mint stem NFT", async () => {
+
+    // one producer with two stems gets one NFT per stem, sent to a wallet the creator picks
+    const recipient = anchor.web3.Keypair.generate().publicKey;
+    const stemPdaFor = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stem"), trackPda.toBuffer(), new anchor.BN(index).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      )[0];
+    const stemNftMintFor = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stem_mint"), stemPdaFor(index).toBuffer()],
+        program.programId
+      )[0];
+
+    for (const cid of ["bafystemvocals", "bafystemvocals2"]) {
+      await program.methods
+        .addStem(trackId, { vocals: {} }, cid, Array(32).fill(2), wallet.publicKey)
+        .accounts({ authority: wallet.publicKey })
+        .rpc();
+    }
+
+    const stemNftMint = stemNftMintFor(1);
+    const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), stemNftMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    const mintStem = (stemIndex: number, signer: anchor.web3.Keypair) =>
+      program.methods
+        .mintStemNft(trackId, stemIndex, "https://example.com/stems/vocals.json", 500)
+        .accounts({
+          payer: wallet.publicKey,
+          authority: signer.publicKey,
+          recipient,
+          collectionMint: null,
+          collectionMetadata: null,
+          collectionMasterEdition: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(signer === wallet.payer ? [] : [signer]);
+
+    // only the stem's creator or the track authority may mint
+    await expect(mintStem(1, anchor.web3.Keypair.generate()).rpc()).rejects.toThrow(/Unauthorized/);
+
+    // retired stems cannot be minted
+    await expect(mintStem(0, wallet.payer).rpc()).rejects.toThrow(/StemRetired/);
+
+    const stemNftMintIx = await mintStem(1, wallet.payer).instruction();
 
     let blockhashContext = await provider.connection.getLatestBlockhash();
 
@@ -335,23 +1691,666 @@ it ("create escrow", async () => {
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
     }).add(stemNftMintIx);
 
-    const signedTx = await anchor.web3.sendAndConfirmTransaction(
+    await anchor.web3.sendAndConfirmTransaction(
       provider.connection,
       tx,
       [wallet.payer]
     );
 
-    console.log("Transaction signature", signedTx);
+    const trackAccount = await program.account.track.fetch(trackPda);
+
+    // the mint is recorded on the Stem account; the track is no longer grown per NFT
+    expect(trackAccount.stemMints.length).to.equal(0);
+
+    const stem = await program.account.stem.fetch(stemPdaFor(1));
+    expect(stem.mint.toBase58()).to.equal(stemNftMint.toBase58());
+    const held = await getAccount(provider.connection, getAssociatedTokenAddressSync(stemNftMint, recipient));
+    expect(held.amount).to.equal(BigInt(1));
+
+    // the second stem by the same creator gets its own NFT; the first cannot be minted twice
+    await mintStem(2, wallet.payer).rpc();
+    expect((await program.account.stem.fetch(stemPdaFor(2))).mint.toBase58()).to.equal(stemNftMintFor(2).toBase58());
+    await expect(mintStem(1, wallet.payer).rpc()).rejects.toThrow();
+
+    // metadata name is "<title> - <stem type>", borsh-encoded after the key, update authority and mint
+    const metadataAccount = await provider.connection.getAccountInfo(metadataPda);
+    expect(metadataAccount.owner.toBase58()).to.equal(TOKEN_METADATA_PROGRAM_ID.toBase58());
+    const nameLen = metadataAccount.data.readUInt32LE(65);
+    const name = metadataAccount.data.subarray(69, 69 + nameLen).toString("utf8").replace(/\0/g, "");
+    expect(name).to.equal("My First Track - Vocals");
+
+    // supply is fixed at one: the master edition holds mint authority and allows no prints
+    const [masterEditionPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), stemNftMint.toBuffer(), Buffer.from("edition")],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const nftMint = await getMint(provider.connection, stemNftMint);
+    expect(nftMint.supply).to.equal(BigInt(1));
+    expect(nftMint.mintAuthority.toBase58()).to.equal(masterEditionPda.toBase58());
+
+
+
+  });
+
+  it("stem NFT metadata lists the five largest shares and rejects Token-2022", async () => {
+    const others = Array.from({ length: 5 }, () => anchor.web3.Keypair.generate().publicKey);
+    // the wallet is listed twice and appears once as a creator with 3000 bps
+    const band = await createTrack(
+      "Big Band",
+      [wallet.publicKey, ...others, wallet.publicKey],
+      [2000, 2500, 1500, 1200, 1000, 800, 1000]
+    );
+    const [stemPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem"), band.pda.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const [stemNftMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem_mint"), stemPda.toBuffer()],
+      program.programId
+    );
+    const [metadataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), stemNftMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    await program.methods
+      .addStem(band.id, { drums: {} }, "bafystemdrums", Array(32).fill(6), wallet.publicKey)
+      .accountsPartial({ authority: wallet.publicKey, track: band.pda })
+      .rpc();
+
+    const mintStem = (tokenProgram: anchor.web3.PublicKey) =>
+      program.methods
+        .mintStemNft(band.id, 0, "https://example.com/stems/drums.json", 500)
+        .accountsPartial({
+          payer: wallet.publicKey,
+          authority: wallet.publicKey,
+          track: band.pda,
+          recipient: wallet.publicKey,
+          collectionMint: null,
+          collectionMetadata: null,
+          collectionMasterEdition: null,
+          tokenProgram,
+        })
+        .rpc();
+
+    await expect(mintStem(TOKEN_2022_PROGRAM_ID)).rejects.toThrow(/StemNftRequiresSplToken/);
+    await mintStem(TOKEN_PROGRAM_ID);
+
+    // walk the borsh strings to the creators list
+    const data = (await provider.connection.getAccountInfo(metadataPda)).data;
+    let offset = 65;
+    for (let i = 0; i < 3; i++) {
+      offset += 4 + data.readUInt32LE(offset);
+    }
+    offset += 2;
+    expect(data[offset]).to.equal(1);
+    const count = data.readUInt32LE(offset + 1);
+    expect(count).to.equal(5);
+
+    const creators = Array.from({ length: count }, (_, i) => {
+      const at = offset + 5 + i * 34;
+      return { address: new anchor.web3.PublicKey(data.subarray(at, at + 32)), share: data[at + 33] };
+    });
+    expect(creators.map((c) => c.address.toBase58())).to.deep.equal(
+      [wallet.publicKey, ...others.slice(0, 4)].map((key) => key.toBase58())
+    );
+    expect(creators.reduce((sum, c) => sum + c.share, 0)).to.equal(100);
+  });
+
+  it("track authority airdrops stem NFTs and creators self-mint", async () => {
+    const collaborator = anchor.web3.Keypair.generate();
+    const stemPdaFor = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stem"), trackPda.toBuffer(), new anchor.BN(index).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      )[0];
+    const stemNftMintFor = (index: number) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stem_mint"), stemPdaFor(index).toBuffer()],
+        program.programId
+      )[0];
+
+    for (const cid of ["bafystembass", "bafystemkeys"]) {
+      await program.methods
+        .addStem(trackId, { bass: {} }, cid, Array(32).fill(4), collaborator.publicKey)
+        .accounts({ authority: wallet.publicKey })
+        .rpc();
+    }
+
+    const mintStem = (stemIndex: number, signer: anchor.web3.Keypair, recipient = collaborator.publicKey) =>
+      program.methods
+        .mintStemNft(trackId, stemIndex, "https://example.com/stems/bass.json", 500)
+        .accounts({
+          payer: wallet.publicKey,
+          authority: signer.publicKey,
+          recipient,
+          collectionMint: null,
+          collectionMetadata: null,
+          collectionMasterEdition: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(signer === wallet.payer ? [] : [signer])
+        .rpc();
+
+    // the collaborator is offline: the track authority mints and the payer creates their ATA
+    await mintStem(3, wallet.payer);
+    const airdropped = await getAccount(
+      provider.connection,
+      getAssociatedTokenAddressSync(stemNftMintFor(3), collaborator.publicKey)
+    );
+    expect(airdropped.amount).to.equal(BigInt(1));
+
+    // airdrops only go to the stem's creator or a listed contributor
+    await expect(mintStem(4, wallet.payer, anchor.web3.Keypair.generate().publicKey)).rejects.toThrow(/NotAContributor/);
+
+    // the collaborator can also mint their own stem, with the payer covering rent
+    await mintStem(4, collaborator);
+    const selfMinted = await getAccount(
+      provider.connection,
+      getAssociatedTokenAddressSync(stemNftMintFor(4), collaborator.publicKey)
+    );
+    expect(selfMinted.amount).to.equal(BigInt(1));
+  });
+
+  it("stem NFTs join the track collection", async () => {
+    const [collectionMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("collection"), trackPda.toBuffer()],
+      program.programId
+    );
+    const [collectionMetadata] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), collectionMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const [collectionMasterEdition] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), collectionMint.toBuffer(), Buffer.from("edition")],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    await program.methods
+      .createTrackCollection(trackId, "https://example.com/tracks/collection.json")
+      .accounts({
+        authority: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
 
     const trackAccount = await program.account.track.fetch(trackPda);
-    console.log("Track Account:", trackAccount);
+    expect(trackAccount.collectionMint.toBase58()).to.equal(collectionMint.toBase58());
+
+    const collection = await getMint(provider.connection, collectionMint);
+    expect(collection.supply).to.equal(BigInt(1));
+
+    // a second collection is rejected
+    await expect(
+      program.methods
+        .createTrackCollection(trackId, "https://example.com/tracks/collection.json")
+        .accounts({ authority: wallet.publicKey, tokenProgram: TOKEN_PROGRAM_ID })
+        .rpc()
+    ).rejects.toThrow();
+
+    // stems minted without the collection accounts are rejected
+    const mintStem = (withCollection: boolean) =>
+      program.methods
+        .mintStemNft(trackId, 5, "https://example.com/stems/drums.json", 500)
+        .accounts({
+          payer: wallet.publicKey,
+          authority: wallet.publicKey,
+          recipient: wallet.publicKey,
+          collectionMint: withCollection ? collectionMint : null,
+          collectionMetadata: withCollection ? collectionMetadata : null,
+          collectionMasterEdition: withCollection ? collectionMasterEdition : null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await program.methods
+      .addStem(trackId, { drums: {} }, "bafystemdrums2", Array(32).fill(3), wallet.publicKey)
+      .accounts({ authority: wallet.publicKey })
+      .rpc();
+
+    await expect(mintStem(false)).rejects.toThrow(/MissingCollectionAccount/);
+    await mintStem(true);
+
+    // the metadata collection field is borsh-encoded as the verified flag followed by the key
+    const [stemPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem"), trackPda.toBuffer(), new anchor.BN(5).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const [stemNftMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem_mint"), stemPda.toBuffer()],
+      program.programId
+    );
+    const [stemMetadata] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), stemNftMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const metadataAccount = await provider.connection.getAccountInfo(stemMetadata);
+    const collectionOffset = metadataAccount.data.indexOf(collectionMint.toBuffer());
+    expect(collectionOffset).to.be.greaterThan(0);
+    expect(metadataAccount.data[collectionOffset - 1]).to.equal(1);
+
+    // stem 1 was minted before the collection existed and is added to it afterwards
+    const [earlyStemPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem"), trackPda.toBuffer(), new anchor.BN(1).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    const [earlyStemMint] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem_mint"), earlyStemPda.toBuffer()],
+      program.programId
+    );
+    const [earlyMetadata] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), earlyStemMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    expect((await provider.connection.getAccountInfo(earlyMetadata)).data.indexOf(collectionMint.toBuffer())).to.equal(-1);
+
+    await program.methods
+      .verifyStemInCollection(trackId, 1)
+      .accounts({ authority: wallet.publicKey, stemMint: earlyStemMint, collectionMint })
+      .rpc();
+
+    const earlyAccount = await provider.connection.getAccountInfo(earlyMetadata);
+    const earlyOffset = earlyAccount.data.indexOf(collectionMint.toBuffer());
+    expect(earlyOffset).to.be.greaterThan(0);
+    expect(earlyAccount.data[earlyOffset - 1]).to.equal(1);
+  });
+
+  it("contributor badges are soulbound", async () => {
+    const mintBadge = (contributor: anchor.web3.PublicKey) =>
+      program.methods
+        .mintContributorBadge(trackId, { producer: {} }, "https://example.com/badges/producer.json")
+        .accounts({
+          authority: wallet.publicKey,
+          contributor,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await expect(mintBadge(anchor.web3.Keypair.generate().publicKey)).rejects.toThrow(/NotAContributor/);
 
-    expect(trackAccount.stemMints.length).to.equal(2);
+    await mintBadge(wallet.publicKey);
 
+    const [badgePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("badge"), trackPda.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const badge = await program.account.contributorBadge.fetch(badgePda);
+    expect(badge.role).to.have.property("producer");
+    expect(badge.contributor.toBase58()).to.equal(wallet.publicKey.toBase58());
 
+    const held = await getAccount(provider.connection, getAssociatedTokenAddressSync(badge.mint, wallet.publicKey));
+    expect(held.amount).to.equal(BigInt(1));
+    expect(held.isFrozen).to.equal(true);
 
+    // frozen for good: the master edition holds freeze authority and there is no delegate to thaw
+    const badgeMint = await getMint(provider.connection, badge.mint);
+    expect(badgeMint.supply).to.equal(BigInt(1));
+    expect(badgeMint.freezeAuthority.toBase58()).not.to.equal(trackPda.toBase58());
+
+    // one badge per contributor
+    await expect(mintBadge(wallet.publicKey)).rejects.toThrow();
   });
 
 
 
+  it ("deposit and distribute native SOL", async () => {
+
+    const [solVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_vault"), trackPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createSolVault(trackId, wallet.publicKey)
+      .accounts({
+        payer: wallet.publicKey,
+      })
+      .rpc();
+
+    const depositLamports = 1_000_000;
+
+    await program.methods
+      .solDeposit(new anchor.BN(depositLamports), trackId, wallet.publicKey)
+      .accounts({
+        payer: wallet.publicKey,
+      })
+      .rpc();
+
+    const vaultAfterDeposit = await provider.connection.getBalance(solVaultPda);
+
+    await program.methods
+      .solDistribute(new anchor.BN(depositLamports), trackId)
+      .accounts({
+        authority: wallet.publicKey,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: wallet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const vaultAfterDistribute = await provider.connection.getBalance(solVaultPda);
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(8 + 32 + 1 + 5 * 8);
+
+    expect(vaultAfterDistribute).to.be.lessThan(vaultAfterDeposit);
+    expect(vaultAfterDistribute).to.be.at.least(rentExempt);
+  });
+
+  it ("holds SOL shares too small for an unfunded wallet as claimable", async () => {
+
+    const newcomer = anchor.web3.Keypair.generate();
+    const side = await createTrack("Side", [wallet.publicKey, newcomer.publicKey], [9990, 10]);
+    const sideEarnings = (contributor: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("earnings"), side.pda.toBuffer(), contributor.toBuffer()],
+        program.programId
+      )[0];
+    const [sideVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_vault"), side.pda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods.createSolVault(side.id, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+    await program.methods.solDeposit(new anchor.BN(1_000_000), side.id, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+
+    // the newcomer's 1_000 lamports would leave their empty account below rent exemption
+    await program.methods
+      .solDistribute(new anchor.BN(1_000_000), side.id)
+      .accountsPartial({ authority: wallet.publicKey, track: side.pda, feeExemption: null })
+      .remainingAccounts([
+        { pubkey: wallet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: sideEarnings(wallet.publicKey), isWritable: true, isSigner: false },
+        { pubkey: newcomer.publicKey, isWritable: true, isSigner: false },
+        { pubkey: sideEarnings(newcomer.publicKey), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    expect(await provider.connection.getBalance(newcomer.publicKey)).to.equal(0);
+    let vault = await program.account.solVault.fetch(sideVault);
+    expect(vault.creditedOutstanding.toNumber()).to.equal(1_000);
+
+    // once funded, the newcomer claims the held share
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: newcomer.publicKey, lamports: 10_000_000 })
+      ),
+      [wallet.payer]
+    );
+    await program.methods
+      .claimSolEarnings(side.id)
+      .accountsPartial({ contributor: newcomer.publicKey, track: side.pda, profile: null })
+      .signers([newcomer])
+      .rpc();
+
+    expect(await provider.connection.getBalance(newcomer.publicKey)).to.equal(10_001_000);
+    vault = await program.account.solVault.fetch(sideVault);
+    expect(vault.creditedOutstanding.toNumber()).to.equal(0);
+  });
+
+  it ("deposit Token-2022 transfer-fee mint records net amount", async () => {
+
+    const feeMint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const mintLamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+    const createFeeMintTx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: wallet.publicKey,
+        newAccountPubkey: feeMint.publicKey,
+        space: mintLen,
+        lamports: mintLamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        feeMint.publicKey,
+        wallet.publicKey,
+        wallet.publicKey,
+        100,
+        BigInt(1_000_000),
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(feeMint.publicKey, 6, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+    );
+    await anchor.web3.sendAndConfirmTransaction(provider.connection, createFeeMintTx, [wallet.payer, feeMint]);
+
+    const feeEscrow = await getAssociatedTokenAddress(feeMint.publicKey, trackPda, true, TOKEN_2022_PROGRAM_ID);
+    const feePayerAccount = await createAccount(
+      provider.connection,
+      wallet.payer,
+      feeMint.publicKey,
+      wallet.publicKey,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(provider.connection, wallet.payer, feeMint.publicKey, feePayerAccount, wallet.payer, 1_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+    await program.methods
+      .addAcceptedMint(trackId)
+      .accounts({
+        authority: wallet.publicKey,
+        mint: feeMint.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods
+      .escrowDeposit(new anchor.BN(1_000_000), trackId, wallet.publicKey, null, null)
+      .accounts({
+        payer: wallet.publicKey,
+        escrowTokenAccount: feeEscrow,
+        payerTokenAccount: feePayerAccount,
+        mint: feeMint.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    const escrowBalance = await provider.connection.getTokenAccountBalance(feeEscrow);
+    expect(Number(escrowBalance.value.amount)).to.equal(990_000);
+  });
+
+  it("stem royalty positions follow the stem NFT holder", async () => {
+    const [mintRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint_registry"), trackPda.toBuffer()],
+      program.programId
+    );
+    const stemIndex = (await program.account.track.fetch(trackPda)).stemCount;
+    const [stemPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("stem"), trackPda.toBuffer(), new anchor.BN(stemIndex).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+
+    await program.methods
+      .addStem(trackId, { synth: {} }, "bafystemsynth", Array(32).fill(5), wallet.publicKey)
+      .accounts({ authority: wallet.publicKey })
+      .rpc();
+
+    const attach = (creator: anchor.web3.Keypair) =>
+      program.methods
+        .attachStemRoyalty(trackId, stemIndex, 2500)
+        .accounts({ authority: wallet.publicKey, creator: creator.publicKey })
+        .signers(creator === wallet.payer ? [] : [creator])
+        .rpc();
+
+    await expect(attach(wallet.payer)).rejects.toThrow(/StemNotMinted/);
+
+    const stemNft = await createMint(provider.connection, wallet.payer, wallet.publicKey, null, 0);
+    const walletNftAccount = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, stemNft, wallet.publicKey);
+    await mintTo(provider.connection, wallet.payer, stemNft, walletNftAccount.address, wallet.payer, 1);
+    await setAuthority(provider.connection, wallet.payer, stemNft, wallet.payer, AuthorityType.MintTokens, trackPda);
+    await program.methods
+      .stemMint(trackId, stemIndex)
+      .accounts({ authority: wallet.publicKey, mint: stemNft, tokenProgram: TOKEN_PROGRAM_ID })
+      .rpc();
+
+    // the carve-out comes out of the stem creator's share, so only they can agree to it
+    await expect(attach(anchor.web3.Keypair.generate())).rejects.toThrow(/Unauthorized/);
+    await attach(wallet.payer);
+
+    let trackAccount = await program.account.track.fetch(trackPda);
+    expect(trackAccount.contributors.map((c) => c.toBase58())).to.deep.equal([wallet.publicKey.toBase58()]);
+    expect(trackAccount.shares).to.deep.equal([7500]);
+    expect(trackAccount.royaltyStems.length).to.equal(1);
+    expect(trackAccount.royaltyStems[0].stem.toBase58()).to.equal(stemPda.toBase58());
+    expect(trackAccount.royaltyStems[0].shareBps).to.equal(2500);
+
+    // the track authority cannot fold the position back into the split on its own
+    await expect(
+      program.methods
+        .updateShares(trackId, [10000], [wallet.publicKey])
+        .accounts({ authority: wallet.publicKey })
+        .rpc()
+    ).rejects.toThrow(/InvalidShareTotal/);
+
+    // selling the NFT moves the royalty position with it
+    const buyer = anchor.web3.Keypair.generate();
+    const buyerNftAccount = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, stemNft, buyer.publicKey);
+    await transfer(provider.connection, wallet.payer, walletNftAccount.address, buyerNftAccount.address, wallet.payer, 1);
+    const buyerAccount = await getOrCreateAssociatedTokenAccount(provider.connection, wallet.payer, mintPublicKey, buyer.publicKey);
+    const contributorAta = await getAssociatedTokenAddress(mintPublicKey, wallet.publicKey, false);
+
+    await fundEscrow(40_000);
+    await program.methods
+      .escrowDistribute(new anchor.BN(40_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: contributorAta, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        { pubkey: stemPda, isWritable: false, isSigner: false },
+        { pubkey: buyerNftAccount.address, isWritable: false, isSigner: false },
+        { pubkey: buyerAccount.address, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(stemPda), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    let buyerBalance = await provider.connection.getTokenAccountBalance(buyerAccount.address);
+    expect(Number(buyerBalance.value.amount)).to.equal(10_000);
+
+    // credited shares are claimed by whoever holds the NFT at claim time
+    await fundEscrow(20_000);
+    await program.methods
+      .escrowCredit(new anchor.BN(20_000), trackId)
+      .accounts({
+        escrowTokenAccount: escrowAta,
+        mint: mintPublicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        feeExemption: null,
+      })
+      .remainingAccounts([
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        { pubkey: earningsPda(stemPda), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+
+    const claim = (holder: anchor.web3.Keypair, nftAccount: anchor.web3.PublicKey, holderAccount: anchor.web3.PublicKey) =>
+      program.methods
+        .claimStemRoyalty(trackId, stemIndex)
+        .accountsPartial({
+          holder: holder.publicKey,
+          track: trackPda,
+          stemNftAccount: nftAccount,
+          mintRegistry: mintRegistryPda,
+          earnings: earningsPda(stemPda),
+          escrowTokenAccount: escrowAta,
+          holderTokenAccount: holderAccount,
+          mint: mintPublicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers(holder === wallet.payer ? [] : [holder])
+        .rpc();
+
+    await expect(claim(wallet.payer, walletNftAccount.address, contributorAta)).rejects.toThrow(/NotStemHolder/);
+    await claim(buyer, buyerNftAccount.address, buyerAccount.address);
+
+    buyerBalance = await provider.connection.getTokenAccountBalance(buyerAccount.address);
+    expect(Number(buyerBalance.value.amount)).to.equal(15_000);
+
+    // a SOL share held back for an unfunded holder is claimed the same way
+    const [solVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sol_vault"), trackPda.toBuffer()],
+      program.programId
+    );
+    await program.methods.solDeposit(new anchor.BN(40_000), trackId, wallet.publicKey).accounts({ payer: wallet.publicKey }).rpc();
+    await program.methods
+      .solDistribute(new anchor.BN(40_000), trackId)
+      .accountsPartial({ authority: wallet.publicKey, track: trackPda, feeExemption: null })
+      .remainingAccounts([
+        { pubkey: wallet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(wallet.publicKey), isWritable: true, isSigner: false },
+        { pubkey: stemPda, isWritable: false, isSigner: false },
+        { pubkey: buyerNftAccount.address, isWritable: false, isSigner: false },
+        { pubkey: buyer.publicKey, isWritable: true, isSigner: false },
+        { pubkey: earningsPda(stemPda), isWritable: true, isSigner: false },
+      ])
+      .rpc();
+    expect((await program.account.solVault.fetch(solVaultPda)).creditedOutstanding.toNumber()).to.equal(10_000);
+
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: wallet.publicKey, toPubkey: buyer.publicKey, lamports: 10_000_000 })
+      ),
+      [wallet.payer]
+    );
+    await program.methods
+      .claimStemRoyaltySol(trackId, stemIndex)
+      .accountsPartial({ holder: buyer.publicKey, track: trackPda, stemNftAccount: buyerNftAccount.address, earnings: earningsPda(stemPda) })
+      .signers([buyer])
+      .rpc();
+
+    expect(await provider.connection.getBalance(buyer.publicKey)).to.equal(10_010_000);
+    expect((await program.account.solVault.fetch(solVaultPda)).creditedOutstanding.toNumber()).to.equal(0);
+
+    // only the current holder can give the position up, handing the share back to the creator
+    const detach = (holder: anchor.web3.Keypair, nftAccount: anchor.web3.PublicKey, recipient: anchor.web3.PublicKey = null) =>
+      program.methods
+        .detachStemRoyalty(trackId, stemIndex, recipient)
+        .accounts({ authority: wallet.publicKey, holder: holder.publicKey, stemNftAccount: nftAccount })
+        .signers(holder === wallet.payer ? [] : [holder])
+        .rpc();
+
+    await expect(detach(wallet.payer, walletNftAccount.address)).rejects.toThrow(/NotStemHolder/);
+
+    // once the creator is off the split, the authority names who takes the share back
+    const [profilePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const standIn = anchor.web3.Keypair.generate().publicKey;
+    const [standInProfile] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), standIn.toBuffer()],
+      program.programId
+    );
+    const profiles = [
+      { pubkey: profilePda, isWritable: true, isSigner: false },
+      { pubkey: standInProfile, isWritable: true, isSigner: false },
+    ];
+    await program.methods
+      .updateShares(trackId, [7500], [standIn])
+      .accounts({ authority: wallet.publicKey })
+      .remainingAccounts(profiles)
+      .rpc();
+
+    await expect(detach(buyer, buyerNftAccount.address)).rejects.toThrow(/NotAContributor/);
+    await detach(buyer, buyerNftAccount.address, standIn);
+
+    trackAccount = await program.account.track.fetch(trackPda);
+    expect(trackAccount.contributors.map((c) => c.toBase58())).to.deep.equal([standIn.toBase58()]);
+    expect(trackAccount.shares).to.deep.equal([10000]);
+    expect(trackAccount.royaltyStems.length).to.equal(0);
+
+    await program.methods
+      .updateShares(trackId, [10000], [wallet.publicKey])
+      .accounts({ authority: wallet.publicKey })
+      .remainingAccounts(profiles)
+      .rpc();
+  });
+
 });
\ No newline at end of file
@@ -0,0 +1,17 @@
+#!/usr/bin/env bash
+# Dumps the mainnet Token Metadata program into the fixture that Anchor.toml loads
+# into the local validator with [[test.genesis]]. Skips the download when the
+# fixture is already there; pass --force to refresh it.
+set -euo pipefail
+
+PROGRAM_ID="metaqbxxUerdq28cj1RbAWkYQm3ybzJb6a8bt518x1s"
+FIXTURE="$(cd "$(dirname "$0")/.." && pwd)/anchor/tests/fixtures/mpl_token_metadata.so"
+
+if [[ -f "$FIXTURE" && "${1:-}" != "--force" ]]; then
+  echo "Token Metadata fixture present: $FIXTURE"
+  exit 0
+fi
+
+mkdir -p "$(dirname "$FIXTURE")"
+solana program dump --url mainnet-beta "$PROGRAM_ID" "$FIXTURE"
+echo "Dumped Token Metadata to $FIXTURE"
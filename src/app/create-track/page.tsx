@@ -5,6 +5,9 @@ import { useSolana } from '@/components/solana/use-solana'
 import { toast } from 'sonner'
 import { useInitializeTrackMutation } from '@/features/muzica/data-access/use-initialize-track-mutation'
 import { useRouter } from 'next/navigation'
+import { useWalletUiGill } from '@wallet-ui/react-gill'
+import { fetchMaybeArtistCounter, MUZICA_PROGRAM_ADDRESS } from '@project/anchor'
+import { address, getAddressEncoder, getProgramDerivedAddress } from 'gill'
 
 type Contributor = {
   address: string
@@ -13,6 +16,7 @@ type Contributor = {
 
 export default function CreateTrack() {
   const { account } = useSolana()
+  const client = useWalletUiGill()
   const router = useRouter()
   const [title, setTitle] = useState('')
   const [cid, setCid] = useState('')
@@ -78,7 +82,13 @@ export default function CreateTrack() {
     }
 
     try {
-      const trackId = BigInt(Date.now())
+      // The program assigns track ids from the artist's counter, so the new track takes its next id
+      const [artistCounter] = await getProgramDerivedAddress({
+        programAddress: MUZICA_PROGRAM_ADDRESS,
+        seeds: ['artist_counter', getAddressEncoder().encode(address(account.address))],
+      })
+      const counter = await fetchMaybeArtistCounter(client.rpc, artistCounter)
+      const trackId = counter.exists ? counter.data.trackCount : BigInt(0)
       const masterHash = new Uint8Array(32) // Placeholder hash
       
       await initializeTrack.mutateAsync({
@@ -4,7 +4,7 @@ import { toastTx } from '@/components/toast-tx'
 import { useSolana } from '@/components/solana/use-solana'
 import { useWalletUiSigner } from '@wallet-ui/react'
 import { useWalletUiSignAndSend, useWalletUiGill } from '@wallet-ui/react-gill'
-import { getInitializeTrackInstruction, MUZICA_PROGRAM_ADDRESS } from '@project/anchor'
+import { getInitializeTrackInstructionAsync, MUZICA_PROGRAM_ADDRESS } from '@project/anchor'
 import { address, getProgramDerivedAddress, getAddressEncoder } from 'gill'
 
 export function useInitializeTrackMutation() {
@@ -37,7 +37,7 @@ export function useInitializeTrackMutation() {
         throw new Error('Wallet not connected')
       }
       // Derive the track PDA
-      // Seeds: [b"track", authority, track_id]
+      // Seeds: [b"track", authority, track_id], where track_id is the artist counter's next id
       const trackIdBytes = new Uint8Array(8)
       const view = new DataView(trackIdBytes.buffer)
       view.setBigUint64(0, trackId, true) // true for little-endian
@@ -55,8 +55,7 @@ export function useInitializeTrackMutation() {
         ],
       })
 
-      const instruction = getInitializeTrackInstruction({
-        trackId,
+      const instruction = await getInitializeTrackInstructionAsync({
         title,
         cid,
         masterHash,